@@ -1,8 +1,21 @@
-use crate::backend::{save_settings, DiscordRPC};
+use crate::backend::{
+    get_stats_directory, run_diagnostics, save_settings, set_scenario_aliases, DiagnosticCheck,
+    SessionSummary,
+};
+#[cfg(feature = "online")]
+use crate::backend::{post_webhook, scenario_key, PostOutcome};
+#[cfg(feature = "tray")]
+use crate::backend::ProfilesFile;
 use crate::state::{AppState, TrayMessage, UiUpdate};
-use crate::ui::{apply_dark_theme, render_main_tab, render_settings_tab, SettingsForm};
-use crate::workers::start_monitoring_thread;
+use crate::ui::{
+    apply_dark_theme, render_calendar_tab, render_diagnostics_tab, render_history_tab,
+    render_main_tab, render_scores_tab, render_sessions_tab, render_settings_tab,
+    render_setup_wizard, ExportFormat, GoalInput, HistoryView, ProfilesForm, ScoresView,
+    SessionsView, SettingsForm, SetupWizard,
+};
+use crate::workers;
 use eframe::egui::{self, RichText, ViewportCommand};
+use parking_lot::Mutex;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Receiver;
 use std::sync::Arc;
@@ -14,10 +27,73 @@ use windows::Win32::Foundation::HWND;
 use windows::Win32::UI::WindowsAndMessaging::{
     FindWindowW, SetForegroundWindow, ShowWindow, SW_HIDE, SW_SHOW,
 };
+#[cfg(feature = "tray")]
+pub struct TrayHandle {
+    pub icon: tray_icon::TrayIcon,
+    /// The "Profile" submenu, refilled on the UI thread when profiles change.
+    pub profile_menu: tray_icon::menu::Submenu,
+    /// Menu ids of the profile entries, read by the tray thread.
+    pub profile_ids: Arc<Mutex<Vec<(tray_icon::menu::MenuId, String)>>>,
+    pub shutdown_tx: std::sync::mpsc::Sender<()>,
+    pub thread: std::thread::JoinHandle<()>,
+}
+#[cfg(feature = "tray")]
+impl TrayHandle {
+    /// Rebuilds the "Profile" submenu, e.g. after a profile was created.
+    pub fn set_profiles(&self, profiles: &ProfilesFile) {
+        while self.profile_menu.remove_at(0).is_some() {}
+        *self.profile_ids.lock() = fill_profile_menu(&self.profile_menu, profiles);
+    }
+}
+/// Adds one entry per profile to `menu`, the active one disabled, returning their ids.
+#[cfg(feature = "tray")]
+pub fn fill_profile_menu(
+    menu: &tray_icon::menu::Submenu,
+    profiles: &ProfilesFile,
+) -> Vec<(tray_icon::menu::MenuId, String)> {
+    let active_profile = profiles.active_name();
+    let mut profile_ids = Vec::new();
+    for name in profiles.names() {
+        let is_active = name == active_profile;
+        let label = if is_active { format!("{} (active)", name) } else { name.clone() };
+        let item = tray_icon::menu::MenuItem::new(label, !is_active, None);
+        if menu.append(&item).is_ok() {
+            profile_ids.push((item.id().clone(), name));
+        }
+    }
+    profile_ids
+}
 #[derive(PartialEq, Clone, Copy)]
 pub enum Tab {
     Main,
+    History,
+    Scores,
+    Sessions,
+    Calendar,
     Settings,
+    Diagnostics,
+}
+impl Tab {
+    const ALL: [Tab; 7] = [
+        Tab::Main,
+        Tab::History,
+        Tab::Scores,
+        Tab::Sessions,
+        Tab::Calendar,
+        Tab::Settings,
+        Tab::Diagnostics,
+    ];
+    fn title(&self) -> &'static str {
+        match self {
+            Tab::Main => "Main",
+            Tab::History => "History",
+            Tab::Scores => "Scores",
+            Tab::Sessions => "Sessions",
+            Tab::Calendar => "Calendar",
+            Tab::Settings => "Settings",
+            Tab::Diagnostics => "Diagnostics",
+        }
+    }
 }
 pub struct KovaaksApp {
     state: Arc<AppState>,
@@ -26,12 +102,20 @@ pub struct KovaaksApp {
     ui_rx: Receiver<UiUpdate>,
 
     settings_form: SettingsForm,
+    profiles_form: ProfilesForm,
+    setup_wizard: Option<SetupWizard>,
+    history_view: HistoryView,
+    scores_view: ScoresView,
+    sessions_view: SessionsView,
+    goal_input: GoalInput,
+    calendar_building: Arc<AtomicBool>,
+    diagnostics: Arc<Mutex<Option<Vec<DiagnosticCheck>>>>,
+    diagnostics_running: Arc<AtomicBool>,
     toast_message: Option<(String, Instant)>,
     is_syncing: bool,
-    tray_icon: Option<tray_icon::TrayIcon>,
+    #[cfg(feature = "tray")]
+    tray: Option<TrayHandle>,
     should_exit: Arc<AtomicBool>,
-    shutdown_tx: Option<std::sync::mpsc::Sender<()>>,
-    tray_thread_handle: Option<std::thread::JoinHandle<()>>,
     #[cfg(windows)]
     window_handle: Option<HWND>,
 }
@@ -41,28 +125,60 @@ impl KovaaksApp {
         state: Arc<AppState>,
         tray_rx: Receiver<TrayMessage>,
         ui_rx: Receiver<UiUpdate>,
-        tray_icon: tray_icon::TrayIcon,
-        shutdown_tx: std::sync::mpsc::Sender<()>,
-        tray_thread_handle: std::thread::JoinHandle<()>,
     ) -> Self {
         let settings = state.settings.lock().clone();
         let settings_form = SettingsForm::from(&settings);
+        let profiles_form = ProfilesForm::from(&state.profiles.load().unwrap_or_default());
+        let setup_wizard = (!settings.setup_complete)
+            .then(|| SetupWizard::new(&settings.installation_path, &settings.webapp_username));
         Self {
             state,
             active_tab: Tab::Main,
             tray_rx,
             ui_rx,
             settings_form,
+            profiles_form,
+            setup_wizard,
+            history_view: HistoryView::default(),
+            scores_view: ScoresView::default(),
+            sessions_view: SessionsView::default(),
+            goal_input: GoalInput::default(),
+            calendar_building: Arc::new(AtomicBool::new(false)),
+            diagnostics: Arc::new(Mutex::new(None)),
+            diagnostics_running: Arc::new(AtomicBool::new(false)),
             toast_message: None,
             is_syncing: false,
-            tray_icon: Some(tray_icon),
+            #[cfg(feature = "tray")]
+            tray: None,
             should_exit: Arc::new(AtomicBool::new(false)),
-            shutdown_tx: Some(shutdown_tx),
-            tray_thread_handle: Some(tray_thread_handle),
             #[cfg(windows)]
             window_handle: None,
         }
     }
+    #[cfg(feature = "tray")]
+    pub fn with_tray(mut self, tray: TrayHandle) -> Self {
+        self.tray = Some(tray);
+        self
+    }
+    fn has_tray(&self) -> bool {
+        #[cfg(feature = "tray")]
+        {
+            self.tray.is_some()
+        }
+        #[cfg(not(feature = "tray"))]
+        {
+            false
+        }
+    }
+    fn shutdown_tray(&mut self) {
+        #[cfg(feature = "tray")]
+        if let Some(tray) = self.tray.take() {
+            drop(tray.icon);
+            let _ = tray.shutdown_tx.send(());
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            let _ = tray.thread.join();
+        }
+    }
 
     #[cfg(windows)]
     fn get_window_handle(&mut self) -> Option<HWND> {
@@ -111,13 +227,14 @@ impl KovaaksApp {
                 TrayMessage::StopRpc => {
                     self.stop_rpc();
                 }
+                TrayMessage::SwitchProfile(name) => {
+                    self.switch_profile(ctx, &name);
+                }
                 TrayMessage::Quit => {
                     self.stop_rpc();
-                    drop(self.tray_icon.take());
+                    self.state.stop_workers();
                     self.should_exit.store(true, Ordering::Relaxed);
-                    if let Some(tx) = self.shutdown_tx.as_ref() {
-                        let _ = tx.send(());
-                    }
+                    self.shutdown_tray();
                     ctx.send_viewport_cmd(ViewportCommand::Close);
                     std::process::exit(0);
                 }
@@ -129,12 +246,15 @@ impl KovaaksApp {
             match update {
                 UiUpdate::RpcStateChanged { .. } => {
 
+                }
+                UiUpdate::SessionStateChanged { .. } => {
+
                 }
                 UiUpdate::ScenarioChanged { .. } => {
 
                 }
                 UiUpdate::ScoresUpdated => {
-
+                    self.history_view.mark_stale();
                 }
                 UiUpdate::SyncProgress { message } => {
                     self.show_toast(&message);
@@ -146,6 +266,11 @@ impl KovaaksApp {
                     }
                     self.show_toast(&message);
                 }
+                UiUpdate::SessionEnded { summary } => {
+                    self.sessions_view.mark_stale();
+                    self.history_view.mark_stale();
+                    self.show_toast(&summary.headline());
+                }
                 UiUpdate::Toast { message } => {
                     self.show_toast(&message);
                 }
@@ -159,73 +284,27 @@ impl KovaaksApp {
         if self.state.is_rpc_running() {
             return;
         }
-        match DiscordRPC::new() {
-            Ok(mut rpc) => {
-                if let Err(e) = rpc.connect() {
-                    self.show_toast(&format!("Failed to connect: {}", e));
-                    return;
-                }
-                *self.state.rpc.lock() = Some(rpc);
-                self.state.rpc_running.store(true, Ordering::Relaxed);
-
-                let now = chrono::Utc::now().timestamp();
-                *self.state.start_time.lock() = Some(now);
-
-                *self.state.session_start_time.lock() = std::time::SystemTime::now();
-                self.state.session_best_scores.lock().clear();
-                self.state.checked_files.lock().clear();
-
-                let state_clone = self.state.clone();
-                std::thread::spawn(move || {
-                    start_monitoring_thread(state_clone);
-                });
-                self.show_toast("Discord RPC started");
-            }
-            Err(e) => {
-                self.show_toast(&format!("Failed to create RPC: {}", e));
-            }
+        match workers::start_rpc(self.state.clone()) {
+            Ok(()) => self.show_toast("Discord RPC started"),
+            Err(e) => self.show_toast(&e),
         }
     }
     fn stop_rpc(&mut self) {
         if !self.state.is_rpc_running() {
             return;
         }
-        self.state.rpc_running.store(false, Ordering::Relaxed);
-        if let Some(mut rpc) = self.state.rpc.lock().take() {
-            let _ = rpc.clear_presence();
-            let _ = rpc.disconnect();
-        }
-        *self.state.start_time.lock() = None;
-        *self.state.current_scenario.lock() = String::new();
-        *self.state.local_highscore.lock() = 0.0;
-        *self.state.session_highscore.lock() = 0.0;
+        workers::stop_rpc(&self.state);
         self.show_toast("Discord RPC stopped");
     }
     fn scan_local_stats(&mut self) {
-        let settings = self.state.settings.lock().clone();
-        let stats_dir = std::path::PathBuf::from(&settings.installation_path).join("stats");
-        if !stats_dir.exists() {
+        if !get_stats_directory(&self.state.settings.lock()).exists() {
             self.show_toast("Stats folder not found");
             return;
         }
-        match crate::backend::scan_all_stats_folder(&stats_dir) {
-            Ok(scores) => {
-                let count = scores.len();
-                if let Err(e) = self.state.local_scores_manager.populate_from_stats_folder(scores) {
-                    self.show_toast(&format!("Failed to save scores: {}", e));
-                } else {
-
-                    if let Ok(all_scores) = self.state.local_scores_manager.get_all_scores() {
-                        *self.state.score_cache.lock() = all_scores;
-                    }
-                    self.show_toast(&format!("Imported {} scenarios", count));
-                }
-            }
-            Err(e) => {
-                self.show_toast(&format!("Scan failed: {}", e));
-            }
-        }
+        workers::start_stats_scan(self.state.clone());
+        self.show_toast("Scanning stats...");
     }
+    #[cfg(feature = "online")]
     fn sync_online_scores(&mut self) {
         if self.is_syncing {
             return;
@@ -238,11 +317,14 @@ impl KovaaksApp {
         self.is_syncing = true;
         self.state.sync_in_progress.store(true, Ordering::Relaxed);
         let state = self.state.clone();
-        std::thread::spawn(move || {
+        self.state.track_worker(std::thread::spawn(move || {
             match state.online_api.fetch_user_scenario_scores(&username) {
                 Ok(online_scores) => {
                     let count = online_scores.len();
-                    *state.online_scores.lock() = online_scores.clone();
+                    *state.online_scores.lock() = online_scores
+                        .iter()
+                        .map(|(name, score)| (scenario_key(name), *score))
+                        .collect();
                     if let Err(e) = state.local_scores_manager.merge_online_scores(online_scores) {
                         state.send_ui_update(UiUpdate::SyncComplete {
                             success: false,
@@ -277,8 +359,9 @@ impl KovaaksApp {
                 }
             }
             state.sync_in_progress.store(false, Ordering::Relaxed);
-        });
+        }));
     }
+    #[cfg(feature = "online")]
     fn reset_sync_flag(&mut self) {
         self.settings_form.online_scores_synced = false;
         {
@@ -288,6 +371,62 @@ impl KovaaksApp {
         }
         self.show_toast("Sync flag reset");
     }
+    /// Takes the wizard's choices into the settings, saves them and starts the first import and sync.
+    fn finish_setup(&mut self, wizard: SetupWizard) {
+        self.settings_form.installation_path = wizard.installation_path.trim().to_string();
+        self.settings_form.webapp_username = wizard.webapp_username.trim().to_string();
+        self.save_settings();
+        if wizard.import_stats {
+            self.scan_local_stats();
+        }
+        #[cfg(feature = "online")]
+        if wizard.sync_online && !self.settings_form.webapp_username.is_empty() {
+            self.sync_online_scores();
+        }
+    }
+    fn create_profile(&mut self, name: &str) {
+        let settings = self.state.settings.lock().clone();
+        match self.state.profiles.create(name, &settings) {
+            Ok(name) => {
+                if let Ok(profiles) = self.state.profiles.load() {
+                    self.profiles_form = ProfilesForm::from(&profiles);
+                    #[cfg(feature = "tray")]
+                    if let Some(tray) = &self.tray {
+                        tray.set_profiles(&profiles);
+                    }
+                }
+                self.profiles_form.selected = name.clone();
+                self.show_toast(&format!("Profile {} created", name));
+            }
+            Err(e) => self.show_toast(&format!("Failed to create profile: {}", e)),
+        }
+    }
+    /// Stores the profile to use and restarts the app, every data file is opened again from its folder.
+    /// The workers are stopped first so nothing writes the old profile's files while the new instance starts.
+    fn switch_profile(&mut self, ctx: &egui::Context, name: &str) {
+        if name == self.profiles_form.active {
+            return;
+        }
+        if let Err(e) = self.state.profiles.switch(name) {
+            self.show_toast(&format!("Failed to switch profile: {}", e));
+            return;
+        }
+        self.stop_rpc();
+        self.state.stop_workers();
+        let relaunched = std::env::current_exe().and_then(|exe| std::process::Command::new(exe).spawn());
+        if let Err(e) = relaunched {
+            let _ = self.state.profiles.switch(&self.profiles_form.active);
+            self.state.shutting_down.store(false, Ordering::Relaxed);
+            #[cfg(feature = "online")]
+            workers::start_webhook_thread(self.state.clone());
+            self.show_toast(&format!("Failed to restart: {}", e));
+            return;
+        }
+        self.should_exit.store(true, Ordering::Relaxed);
+        self.shutdown_tray();
+        ctx.send_viewport_cmd(ViewportCommand::Close);
+        std::process::exit(0);
+    }
     fn save_settings(&mut self) {
         let last_sync_time = self.state.settings.lock().last_sync_time;
         let new_settings = self.settings_form.to_settings(last_sync_time);
@@ -295,35 +434,143 @@ impl KovaaksApp {
             self.show_toast(&format!("Failed to save: {}", e));
             return;
         }
+        set_scenario_aliases(&new_settings.scenario_aliases);
+        let problems = new_settings.validate();
         *self.state.settings.lock() = new_settings;
-        self.show_toast("Settings saved");
+        if let Ok(all_scores) = self.state.local_scores_manager.get_all_scores() {
+            *self.state.score_cache.lock() = all_scores;
+        }
+        self.state.refresh_current_highscore();
+        match problems.as_slice() {
+            [] => self.show_toast("Settings saved"),
+            [problem] => self.show_toast(&format!("Settings saved, but: {}", problem.message)),
+            problems => {
+                let messages: Vec<&str> = problems.iter().map(|problem| problem.message.as_str()).collect();
+                self.show_toast(&format!(
+                    "Settings saved, but {} problems need fixing (see the Diagnostics tab): {}",
+                    problems.len(),
+                    messages.join(", ")
+                ));
+            }
+        }
+    }
+    fn export_session(&mut self, summary: &SessionSummary, format: ExportFormat) {
+        let (extension, contents) = match format {
+            ExportFormat::Markdown => ("md", Ok(summary.to_markdown())),
+            ExportFormat::Json => ("json", summary.to_json()),
+        };
+        let contents = match contents {
+            Ok(contents) => contents,
+            Err(e) => {
+                self.show_toast(&format!("Export failed: {}", e));
+                return;
+            }
+        };
+        let file_name = format!("kovaaks-session-{}.{}", summary.started_at, extension);
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(&file_name)
+            .add_filter(extension, &[extension])
+            .save_file()
+        else {
+            return;
+        };
+        match std::fs::write(&path, contents) {
+            Ok(()) => self.show_toast("Session exported"),
+            Err(e) => self.show_toast(&format!("Export failed: {}", e)),
+        }
+    }
+    #[cfg(feature = "online")]
+    fn test_webhook(&mut self) {
+        let url = self.settings_form.webhook_url.trim().to_string();
+        let state = self.state.clone();
+        std::thread::spawn(move || {
+            let payload = serde_json::json!({
+                "content": "Kovaaks Discord RPC webhook is working",
+            });
+            let message = match post_webhook(&url, &payload) {
+                PostOutcome::Sent => "Test message sent".to_string(),
+                PostOutcome::RateLimited(_) => "Webhook is rate limited, try again shortly".to_string(),
+                PostOutcome::Retry(reason) | PostOutcome::Rejected(reason) => {
+                    format!("Webhook failed: {}", reason)
+                }
+            };
+            state.send_ui_update(UiUpdate::Toast { message });
+        });
+        self.show_toast("Sending test message...");
+    }
+    fn import_goals(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("CSV", &["csv"]).pick_file() else {
+            return;
+        };
+        match self.state.local_scores_manager.import_goals_csv(&path) {
+            Ok(count) => {
+                if let Ok(all_scores) = self.state.local_scores_manager.get_all_scores() {
+                    *self.state.score_cache.lock() = all_scores;
+                }
+                self.show_toast(&format!("Imported {} goals", count));
+            }
+            Err(e) => self.show_toast(&format!("Goal import failed: {}", e)),
+        }
+    }
+    fn run_diagnostics(&mut self) {
+        if self.diagnostics_running.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let settings = self.state.settings.lock().clone();
+        let diagnostics = self.diagnostics.clone();
+        let running = self.diagnostics_running.clone();
+        std::thread::spawn(move || {
+            let checks = run_diagnostics(&settings);
+            *diagnostics.lock() = Some(checks);
+            running.store(false, Ordering::Relaxed);
+        });
+    }
+    fn refresh_calendar(&mut self) {
+        if self.calendar_building.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let state = self.state.clone();
+        let building = self.calendar_building.clone();
+        std::thread::spawn(move || {
+            state.refresh_calendar();
+            building.store(false, Ordering::Relaxed);
+        });
     }
     fn render_navbar(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             ui.add_space(16.0);
-            let main_selected = self.active_tab == Tab::Main;
-            let main_text = RichText::new("Main")
-                .size(14.0)
-                .color(if main_selected {
-                    crate::ui::TEXT_WHITE
-                } else {
-                    crate::ui::TEXT_MUTED
-                });
-            if ui.add(egui::Button::new(main_text).frame(false)).clicked() {
-                self.active_tab = Tab::Main;
-            }
-            ui.add_space(16.0);
-            let settings_selected = self.active_tab == Tab::Settings;
-            let settings_text = RichText::new("Settings")
-                .size(14.0)
-                .color(if settings_selected {
-                    crate::ui::TEXT_WHITE
-                } else {
-                    crate::ui::TEXT_MUTED
-                });
-            if ui.add(egui::Button::new(settings_text).frame(false)).clicked() {
-                self.active_tab = Tab::Settings;
+            for tab in Tab::ALL {
+                let selected = self.active_tab == tab;
+                let text = RichText::new(tab.title())
+                    .size(14.0)
+                    .color(if selected {
+                        crate::ui::TEXT_WHITE
+                    } else {
+                        crate::ui::TEXT_MUTED
+                    });
+                if ui.add(egui::Button::new(text).frame(false)).clicked() {
+                    match tab {
+                        Tab::History => self.history_view.mark_stale(),
+                        Tab::Sessions => self.sessions_view.mark_stale(),
+                        Tab::Calendar if self.state.training_calendar.lock().is_none() => {
+                            self.refresh_calendar();
+                        }
+                        Tab::Diagnostics => self.run_diagnostics(),
+                        _ => {}
+                    }
+                    self.active_tab = tab;
+                }
+                ui.add_space(16.0);
             }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.add_space(16.0);
+                let text = RichText::new(format!("Profile: {}", self.profiles_form.active))
+                    .size(12.0)
+                    .color(crate::ui::TEXT_MUTED);
+                if ui.add(egui::Button::new(text).frame(false)).clicked() {
+                    self.active_tab = Tab::Settings;
+                }
+            });
         });
         ui.separator();
     }
@@ -354,7 +601,7 @@ impl eframe::App for KovaaksApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
 
         if ctx.input(|i| i.viewport().close_requested()) {
-            if !self.should_exit.load(Ordering::Relaxed) {
+            if self.has_tray() && !self.should_exit.load(Ordering::Relaxed) {
                 ctx.send_viewport_cmd(ViewportCommand::CancelClose);
                 #[cfg(windows)]
                 self.hide_window();
@@ -374,6 +621,22 @@ impl eframe::App for KovaaksApp {
             }
         }
 
+        if let Some(wizard) = self.setup_wizard.as_mut() {
+            let action = egui::CentralPanel::default()
+                .show(ctx, |ui| render_setup_wizard(ui, wizard))
+                .inner;
+            if action.finish {
+                if let Some(wizard) = self.setup_wizard.take() {
+                    self.finish_setup(wizard);
+                }
+            } else if action.skip {
+                self.setup_wizard = None;
+                self.save_settings();
+            }
+            self.render_toast(ctx);
+            return;
+        }
+
         egui::TopBottomPanel::top("navbar").show(ctx, |ui| {
             ui.add_space(8.0);
             self.render_navbar(ui);
@@ -383,7 +646,14 @@ impl eframe::App for KovaaksApp {
         egui::CentralPanel::default().show(ctx, |ui| {
             match self.active_tab {
                 Tab::Main => {
-                    let action = render_main_tab(ui, &self.state);
+                    let action = render_main_tab(ui, &self.state, &mut self.goal_input);
+                    if let Some((scenario, goal)) = action.set_goal {
+                        match self.state.set_goal(&scenario, goal) {
+                            Ok(()) if goal.is_some() => self.show_toast("Goal saved"),
+                            Ok(()) => self.show_toast("Goal cleared"),
+                            Err(e) => self.show_toast(&format!("Failed to save goal: {}", e)),
+                        }
+                    }
                     if action.start_rpc {
                         self.start_rpc();
                     }
@@ -391,24 +661,96 @@ impl eframe::App for KovaaksApp {
                         self.stop_rpc();
                     }
                     if action.minimize {
-                        #[cfg(windows)]
-                        self.hide_window();
+                        if self.has_tray() {
+                            #[cfg(windows)]
+                            self.hide_window();
+                        } else {
+                            ctx.send_viewport_cmd(ViewportCommand::Minimized(true));
+                        }
+                    }
+                }
+                Tab::History => {
+                    render_history_tab(ui, &mut self.history_view, &self.state.run_history);
+                }
+                Tab::Scores => {
+                    let scores = self.state.score_cache.lock().clone();
+                    render_scores_tab(ui, &mut self.scores_view, &scores);
+                }
+                Tab::Sessions => {
+                    let action = render_sessions_tab(ui, &mut self.sessions_view, &self.state.session_log);
+                    if let Some((summary, format)) = action.export {
+                        self.export_session(&summary, format);
+                    }
+                }
+                Tab::Calendar => {
+                    let calendar = self.state.training_calendar.lock().clone();
+                    let is_building = self.calendar_building.load(Ordering::Relaxed);
+                    let action = render_calendar_tab(ui, calendar.as_ref(), is_building);
+                    if action.refresh {
+                        self.refresh_calendar();
+                    }
+                }
+                Tab::Diagnostics => {
+                    let checks = self.diagnostics.lock().clone();
+                    let is_running = self.diagnostics_running.load(Ordering::Relaxed);
+                    let action = render_diagnostics_tab(ui, checks.as_deref(), is_running);
+                    if action.run {
+                        self.run_diagnostics();
                     }
                 }
                 Tab::Settings => {
-                    let action = render_settings_tab(ui, &mut self.settings_form, self.is_syncing);
+                    let validation_entries = self.state.scenario_validation_cache.lock().entries();
+                    let action = render_settings_tab(
+                        ui,
+                        &mut self.settings_form,
+                        &mut self.profiles_form,
+                        self.is_syncing,
+                        self.state.stats_scan_in_progress.load(Ordering::Relaxed),
+                        &validation_entries,
+                    );
                     if action.scan_stats {
                         self.scan_local_stats();
                     }
+                    if action.cancel_scan {
+                        workers::cancel_stats_scan(&self.state);
+                    }
+                    #[cfg(feature = "online")]
                     if action.sync_online {
                         self.sync_online_scores();
                     }
+                    #[cfg(feature = "online")]
                     if action.reset_sync {
                         self.reset_sync_flag();
                     }
                     if action.save {
                         self.save_settings();
                     }
+                    if action.import_goals {
+                        self.import_goals();
+                    }
+                    if let Some(name) = action.create_profile {
+                        self.create_profile(&name);
+                    }
+                    if let Some(name) = action.switch_profile {
+                        self.switch_profile(ctx, &name);
+                    }
+                    #[cfg(feature = "online")]
+                    if action.test_webhook {
+                        self.test_webhook();
+                    }
+                    if let Some((name, manual_override)) = action.validation_override {
+                        let _ = self.state.scenario_validation_cache.lock().set_override(&name, manual_override);
+                    }
+                    if let Some(name) = action.validation_refresh {
+                        let _ = self.state.scenario_validation_cache.lock().remove(&name);
+                        self.show_toast(&format!("{} will be rechecked", name));
+                    }
+                    if action.purge_validation_cache {
+                        let purged = self.state.scenario_validation_cache.lock().purge_expired();
+                        if let Ok(removed) = purged {
+                            self.show_toast(&format!("Removed {} expired entries", removed));
+                        }
+                    }
                 }
             }
         });
@@ -417,13 +759,7 @@ impl eframe::App for KovaaksApp {
     }
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
         self.stop_rpc();
-        drop(self.tray_icon.take());
-        if let Some(tx) = self.shutdown_tx.take() {
-            let _ = tx.send(());
-            std::thread::sleep(std::time::Duration::from_millis(50));
-        }
-        if let Some(handle) = self.tray_thread_handle.take() {
-            let _ = handle.join();
-        }
+        self.state.stop_workers();
+        self.shutdown_tray();
     }
 }
\ No newline at end of file
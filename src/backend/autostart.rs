@@ -1,3 +1,4 @@
+//! Registering the app to start with Windows.
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 #[cfg(target_os = "windows")]
 pub fn get_autostart_enabled() -> bool {
@@ -0,0 +1,73 @@
+//! Runs and playtime per day, and training streaks.
+use crate::backend::stats_filename::StatsFileName;
+use crate::backend::stats_index::StatsIndexFile;
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+/// What was played on one day.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DayStats {
+    pub runs: u32,
+    pub playtime_secs: f64,
+}
+/// Training days keyed by local date.
+#[derive(Debug, Clone, Default)]
+pub struct TrainingCalendar {
+    days: BTreeMap<NaiveDate, DayStats>,
+}
+impl TrainingCalendar {
+    /// Builds the calendar from the stats index, dating runs by the timestamp in their file name.
+    pub fn from_index(index: &StatsIndexFile) -> Self {
+        let mut calendar = Self::default();
+        for (file_name, entry) in &index.entries {
+            let Some(played_at) = StatsFileName::parse(file_name).and_then(|parsed| parsed.played_at_system_time()) else {
+                continue;
+            };
+            calendar.add_run(played_at, entry.fight_time.unwrap_or(0.0));
+        }
+        calendar
+    }
+    pub fn add_run(&mut self, played_at: SystemTime, fight_time_secs: f64) {
+        let date = DateTime::<Local>::from(played_at).date_naive();
+        let day = self.days.entry(date).or_default();
+        day.runs += 1;
+        day.playtime_secs += fight_time_secs.max(0.0);
+    }
+    pub fn day(&self, date: NaiveDate) -> DayStats {
+        self.days.get(&date).copied().unwrap_or_default()
+    }
+    pub fn days(&self) -> impl Iterator<Item = (&NaiveDate, &DayStats)> {
+        self.days.iter()
+    }
+    pub fn max_runs_per_day(&self) -> u32 {
+        self.days.values().map(|day| day.runs).max().unwrap_or(0)
+    }
+    /// Consecutive training days up to today, still counting yesterday's streak if today has no runs yet.
+    pub fn current_streak(&self, today: NaiveDate) -> u32 {
+        let mut date = if self.days.contains_key(&today) {
+            today
+        } else {
+            today - Duration::days(1)
+        };
+        let mut streak = 0;
+        while self.days.contains_key(&date) {
+            streak += 1;
+            date -= Duration::days(1);
+        }
+        streak
+    }
+    pub fn longest_streak(&self) -> u32 {
+        let mut longest = 0;
+        let mut current = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for date in self.days.keys() {
+            current = match previous {
+                Some(prev) if *date - prev == Duration::days(1) => current + 1,
+                _ => 1,
+            };
+            longest = longest.max(current);
+            previous = Some(*date);
+        }
+        longest
+    }
+}
\ No newline at end of file
@@ -0,0 +1,112 @@
+//! Importing runs played while the app was closed (`catch_up.json` remembers the newest file seen).
+use crate::backend::kovaaks_utils::scan_stats_folder_since;
+use crate::backend::local_scores::LocalScoresManager;
+use crate::backend::run_history::{RunHistoryManager, RunRecord};
+use crate::backend::stats_filename::list_stats_files;
+use crate::backend::stats_parser::{read_stats_file, StatsRead};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+/// The newest stats file already imported.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CatchUpMarker {
+    #[serde(default)]
+    pub newest_file: Option<String>,
+    /// Unix time from the newest file's name.
+    #[serde(default)]
+    pub newest_played_at: Option<u64>,
+}
+/// What a catch-up import added.
+#[derive(Debug, Clone, Default)]
+pub struct CatchUpReport {
+    pub runs: usize,
+    pub scenarios: usize,
+    /// Names of stats files that couldn't be read.
+    pub unreadable_files: Vec<String>,
+}
+/// Reads and writes the catch-up marker, every call goes to disk.
+pub struct CatchUpManager {
+    file_path: PathBuf,
+}
+impl CatchUpManager {
+    pub fn new() -> Result<Self> {
+        let app_data_dir = crate::backend::get_app_data_dir();
+        let _ = fs::create_dir_all(&app_data_dir);
+        let file_path = app_data_dir.join("catch_up.json");
+        Ok(Self { file_path })
+    }
+    pub fn load(&self) -> Result<CatchUpMarker> {
+        if !self.file_path.exists() {
+            return Ok(CatchUpMarker::default());
+        }
+        let contents = fs::read_to_string(&self.file_path)?;
+        Ok(serde_json::from_str(&contents).unwrap_or_default())
+    }
+    pub fn save(&self, marker: &CatchUpMarker) -> Result<()> {
+        let json = serde_json::to_string_pretty(marker)?;
+        let mut tmp_path = self.file_path.clone();
+        tmp_path.set_extension("tmp");
+        fs::write(&tmp_path, &json)?;
+        fs::rename(&tmp_path, &self.file_path)?;
+        Ok(())
+    }
+    /// Imports every run newer than the marker into the score store and run history.
+    /// The first time only the marker is set, the Scan Stats button covers older runs.
+    pub fn import_since_last_launch(
+        &self,
+        stats_dir: &Path,
+        scores: &LocalScoresManager,
+        history: &RunHistoryManager,
+        dpi: u32,
+    ) -> Result<CatchUpReport> {
+        let files = list_stats_files(stats_dir);
+        let Some((newest_path, newest)) = files
+            .iter()
+            .filter_map(|(path, parsed)| Some((path, parsed.played_at_unix()?)))
+            .max_by_key(|(_, played_at)| *played_at)
+        else {
+            return Ok(CatchUpReport::default());
+        };
+        let marker = self.load()?;
+        let new_marker = CatchUpMarker {
+            newest_file: newest_path.file_name().map(|name| name.to_string_lossy().to_string()),
+            newest_played_at: Some(newest),
+        };
+        let Some(since) = marker.newest_played_at else {
+            self.save(&new_marker)?;
+            return Ok(CatchUpReport::default());
+        };
+        if newest <= since {
+            return Ok(CatchUpReport::default());
+        }
+        let scan = scan_stats_folder_since(stats_dir, Some(since))?;
+        if !scan.scores.is_empty() {
+            scores.populate_from_stats_folder(scan.scores)?;
+        }
+        let mut report = CatchUpReport {
+            unreadable_files: scan.unreadable_files,
+            ..CatchUpReport::default()
+        };
+        let mut runs: HashMap<String, Vec<RunRecord>> = HashMap::new();
+        for (path, parsed) in files {
+            if !parsed.played_at_unix().is_some_and(|played_at| played_at > since) {
+                continue;
+            }
+            if let StatsRead::Run(run) = read_stats_file(&path) {
+                let record = RunRecord::from_run(&run, parsed.played_at_system_time(), Some(parsed.mode), dpi);
+                runs.entry(parsed.scenario).or_default().push(record);
+            }
+        }
+        for (scenario, records) in runs {
+            let added = history.add_runs(&scenario, records)?;
+            if added > 0 {
+                report.runs += added;
+                report.scenarios += 1;
+            }
+        }
+        self.save(&new_marker)?;
+        Ok(report)
+    }
+}
\ No newline at end of file
@@ -1,7 +1,101 @@
-use serde::{Deserialize, Serialize};
+//! Persisted user settings.
+use crate::backend::stats_filename::RunMode;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+/// Which run modes count toward the displayed highscore.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum CountedModes {
+    #[default]
+    Both,
+    ChallengeOnly,
+    FreeplayOnly,
+}
+impl CountedModes {
+    pub const ALL: [CountedModes; 3] = [
+        CountedModes::Both,
+        CountedModes::ChallengeOnly,
+        CountedModes::FreeplayOnly,
+    ];
+    pub fn counts(&self, mode: RunMode) -> bool {
+        match self {
+            CountedModes::Both => true,
+            CountedModes::ChallengeOnly => mode == RunMode::Challenge,
+            CountedModes::FreeplayOnly => mode == RunMode::Freeplay,
+        }
+    }
+    /// Like [`CountedModes::counts`] for a recorded run, runs without a mode only count when both modes do.
+    pub fn counts_run(&self, mode: Option<RunMode>) -> bool {
+        match mode {
+            Some(mode) => self.counts(mode),
+            None => *self == CountedModes::Both,
+        }
+    }
+    pub fn label(&self) -> &'static str {
+        match self {
+            CountedModes::Both => "Challenge and Freeplay",
+            CountedModes::ChallengeOnly => "Challenge only",
+            CountedModes::FreeplayOnly => "Freeplay only",
+        }
+    }
+}
+/// Which highscore is shown when both a local and an online one are known.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ScoreDisplay {
+    #[default]
+    Local,
+    Online,
+    Max,
+    Both,
+}
+impl ScoreDisplay {
+    pub const ALL: [ScoreDisplay; 4] = [
+        ScoreDisplay::Local,
+        ScoreDisplay::Online,
+        ScoreDisplay::Max,
+        ScoreDisplay::Both,
+    ];
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScoreDisplay::Local => "Local",
+            ScoreDisplay::Online => "Online",
+            ScoreDisplay::Max => "Higher of the two",
+            ScoreDisplay::Both => "Both",
+        }
+    }
+}
+/// Reads `show_online_scores` from older settings files, where it was a checkbox that showed the higher score.
+fn deserialize_score_display<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<ScoreDisplay, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Flag(bool),
+        Display(ScoreDisplay),
+    }
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Flag(true) => ScoreDisplay::Max,
+        Stored::Flag(false) => ScoreDisplay::Local,
+        Stored::Display(display) => display,
+    })
+}
+/// A setting that can't work as entered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettingsProblem {
+    /// Name of the field in `settings.json`.
+    pub field: &'static str,
+    pub message: String,
+}
+impl SettingsProblem {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            message: message.into(),
+        }
+    }
+}
+/// User settings stored as `settings.json` in the app data directory.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub installation_path: String,
@@ -9,12 +103,63 @@ pub struct Settings {
     pub open_manually: bool,
     pub start_with_windows: bool,
     pub webapp_username: String,
-    pub show_online_scores: bool,
+    #[serde(default, deserialize_with = "deserialize_score_display")]
+    pub show_online_scores: ScoreDisplay,
     pub start_in_tray: bool,
     pub online_only_scenarios: bool,
     pub online_scores_synced: bool,
     #[serde(default)]
     pub last_sync_time: u64,
+    #[serde(default = "default_idle_timeout_minutes")]
+    pub idle_timeout_minutes: u64,
+    #[serde(default)]
+    pub clear_presence_when_idle: bool,
+    #[serde(default)]
+    pub scenario_allowlist: Vec<String>,
+    #[serde(default)]
+    pub scenario_blocklist: Vec<String>,
+    #[serde(default)]
+    pub hide_scores: bool,
+    #[serde(default)]
+    pub streamer_mode: bool,
+    #[serde(default = "default_presence_details_template")]
+    pub presence_details_template: String,
+    #[serde(default = "default_presence_state_template")]
+    pub presence_state_template: String,
+    #[serde(default = "default_mouse_dpi")]
+    pub mouse_dpi: u32,
+    #[serde(default)]
+    pub webhook_url: String,
+    #[serde(default = "default_true")]
+    pub webhook_post_pbs: bool,
+    #[serde(default = "default_true")]
+    pub webhook_post_sessions: bool,
+    #[serde(default)]
+    pub obs_output_enabled: bool,
+    #[serde(default)]
+    pub obs_output_dir: String,
+    #[serde(default)]
+    pub counted_modes: CountedModes,
+    #[serde(default)]
+    pub scenario_aliases: BTreeMap<String, String>,
+    /// Cleared only for new installs, so the setup wizard isn't shown to existing users.
+    #[serde(default = "default_true")]
+    pub setup_complete: bool,
+}
+fn default_idle_timeout_minutes() -> u64 {
+    15
+}
+fn default_presence_details_template() -> String {
+    "Playing: {scenario}".to_string()
+}
+fn default_presence_state_template() -> String {
+    "Highscore: {highscore}".to_string()
+}
+fn default_mouse_dpi() -> u32 {
+    800
+}
+fn default_true() -> bool {
+    true
 }
 impl Default for Settings {
     fn default() -> Self {
@@ -24,19 +169,124 @@ impl Default for Settings {
             open_manually: false,
             start_with_windows: false,
             webapp_username: String::new(),
-            show_online_scores: false,
+            show_online_scores: ScoreDisplay::Local,
             start_in_tray: false,
             online_only_scenarios: false,
             online_scores_synced: false,
             last_sync_time: 0,
+            idle_timeout_minutes: default_idle_timeout_minutes(),
+            clear_presence_when_idle: false,
+            scenario_allowlist: Vec::new(),
+            scenario_blocklist: Vec::new(),
+            hide_scores: false,
+            streamer_mode: false,
+            presence_details_template: default_presence_details_template(),
+            presence_state_template: default_presence_state_template(),
+            mouse_dpi: default_mouse_dpi(),
+            webhook_url: String::new(),
+            webhook_post_pbs: true,
+            webhook_post_sessions: true,
+            obs_output_enabled: false,
+            obs_output_dir: String::new(),
+            counted_modes: CountedModes::Both,
+            scenario_aliases: BTreeMap::new(),
+            setup_complete: false,
         }
     }
 }
+impl Settings {
+    /// Everything that won't work with these settings, empty when they are fine.
+    pub fn validate(&self) -> Vec<SettingsProblem> {
+        let mut problems = Vec::new();
+        let installation_path = Path::new(self.installation_path.trim());
+        if self.installation_path.trim().is_empty() {
+            problems.push(SettingsProblem::new("installation_path", "KovaaK's folder is not set"));
+        } else if !installation_path.is_dir() {
+            problems.push(SettingsProblem::new(
+                "installation_path",
+                format!("KovaaK's folder {} doesn't exist", installation_path.display()),
+            ));
+        } else if !installation_path.join("stats").is_dir() {
+            problems.push(SettingsProblem::new(
+                "installation_path",
+                format!("{} has no stats folder, pick the FPSAimTrainer folder inside it", installation_path.display()),
+            ));
+        }
+        if !self.open_manually {
+            let steam_path = Path::new(self.steam_path.trim());
+            if self.steam_path.trim().is_empty() {
+                problems.push(SettingsProblem::new("steam_path", "Steam path is not set"));
+            } else if !steam_path.is_file() {
+                problems.push(SettingsProblem::new(
+                    "steam_path",
+                    format!("Steam was not found at {}", steam_path.display()),
+                ));
+            }
+        }
+        if !(100..=32000).contains(&self.mouse_dpi) {
+            problems.push(SettingsProblem::new("mouse_dpi", "Mouse DPI must be between 100 and 32000"));
+        }
+        let webhook_url = self.webhook_url.trim();
+        if !webhook_url.is_empty() && !is_discord_webhook_url(webhook_url) {
+            problems.push(SettingsProblem::new("webhook_url", "Webhook URL is not a Discord webhook URL"));
+        }
+        if let Some(obs_dir) = self.obs_output_directory() {
+            if obs_dir.exists() && !obs_dir.is_dir() {
+                problems.push(SettingsProblem::new(
+                    "obs_output_dir",
+                    format!("OBS output folder {} is a file", obs_dir.display()),
+                ));
+            }
+        }
+        if self.presence_details_template.trim().is_empty() && self.presence_state_template.trim().is_empty() {
+            problems.push(SettingsProblem::new("presence_details_template", "Both presence texts are empty"));
+        }
+        problems
+    }
+    /// Inactivity period after which presence switches to idle, `None` when disabled.
+    pub fn idle_timeout(&self) -> Option<std::time::Duration> {
+        if self.idle_timeout_minutes == 0 {
+            return None;
+        }
+        Some(std::time::Duration::from_secs(self.idle_timeout_minutes * 60))
+    }
+    /// Directory for the OBS text files, the app data `obs` folder when none is set.
+    pub fn obs_output_directory(&self) -> Option<PathBuf> {
+        if !self.obs_output_enabled {
+            return None;
+        }
+        if self.obs_output_dir.trim().is_empty() {
+            return Some(crate::backend::get_app_data_dir().join("obs"));
+        }
+        Some(PathBuf::from(self.obs_output_dir.trim()))
+    }
+}
+/// Whether `url` is an HTTPS Discord webhook, on any Discord subdomain (`ptb.`, `canary.`) and API version.
+fn is_discord_webhook_url(url: &str) -> bool {
+    let Some((host, path)) = url.strip_prefix("https://").and_then(|rest| rest.split_once('/')) else {
+        return false;
+    };
+    let host = host.to_ascii_lowercase();
+    let discord_host = ["discord.com", "discordapp.com"]
+        .iter()
+        .any(|domain| host == *domain || host.strip_suffix(domain).is_some_and(|sub| sub.ends_with('.')));
+    let Some(path) = path.strip_prefix("api/") else {
+        return false;
+    };
+    let path = match path.split_once('/') {
+        Some((version, rest)) if version.len() > 1 && version.starts_with('v') && version[1..].chars().all(|c| c.is_ascii_digit()) => {
+            rest
+        }
+        _ => path,
+    };
+    discord_host && path.strip_prefix("webhooks/").is_some_and(|id| !id.is_empty())
+}
 fn get_settings_path() -> PathBuf {
     let app_data_dir = crate::backend::get_app_data_dir();
     let _ = fs::create_dir_all(&app_data_dir);
     app_data_dir.join("settings.json")
 }
+/// Loads settings from disk, falling back to defaults when the file is missing.
 pub fn load_settings() -> Result<Settings> {
     let settings_path = get_settings_path();
     if let Ok(contents) = fs::read_to_string(&settings_path) {
@@ -48,8 +298,14 @@ pub fn load_settings() -> Result<Settings> {
         Ok(Settings::default())
     }
 }
+/// Atomically writes settings to disk.
 pub fn save_settings(settings: &Settings) -> Result<()> {
-    let settings_path = get_settings_path();
+    save_settings_in(&crate::backend::get_app_data_dir(), settings)
+}
+/// Atomically writes settings into the given data folder, e.g. that of a new profile.
+pub fn save_settings_in(app_data_dir: &Path, settings: &Settings) -> Result<()> {
+    fs::create_dir_all(app_data_dir)?;
+    let settings_path = app_data_dir.join("settings.json");
     let json = serde_json::to_string_pretty(settings)?;
     let mut tmp = settings_path.clone();
     tmp.set_extension("tmp");
@@ -57,29 +313,32 @@ pub fn save_settings(settings: &Settings) -> Result<()> {
     fs::rename(&tmp, &settings_path)?;
     Ok(())
 }
+/// Steam's own folder as recorded in the registry.
 #[cfg(target_os = "windows")]
-pub fn get_steam_path_from_registry() -> Option<String> {
+pub fn get_steam_root_from_registry() -> Option<PathBuf> {
     use winreg::enums::*;
     use winreg::RegKey;
     let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-    if let Ok(steam_key) = hklm.open_subkey(r"SOFTWARE\WOW6432Node\Valve\Steam") {
-        if let Ok(install_path) = steam_key.get_value::<String, _>("InstallPath") {
-            let candidate = PathBuf::from(&install_path)
-                .join("steamapps")
-                .join("common")
-                .join("FPSAimTrainer")
-                .join("FPSAimTrainer");
-            if candidate.join("stats").exists() {
-                return Some(candidate.to_string_lossy().to_string());
-            }
-        }
-    }
-    None
+    let steam_key = hklm.open_subkey(r"SOFTWARE\WOW6432Node\Valve\Steam").ok()?;
+    let install_path = steam_key.get_value::<String, _>("InstallPath").ok()?;
+    Some(PathBuf::from(install_path))
 }
 #[cfg(not(target_os = "windows"))]
+pub fn get_steam_root_from_registry() -> Option<PathBuf> {
+    None
+}
 pub fn get_steam_path_from_registry() -> Option<String> {
+    let candidate = get_steam_root_from_registry()?
+        .join("steamapps")
+        .join("common")
+        .join("FPSAimTrainer")
+        .join("FPSAimTrainer");
+    if candidate.join("stats").exists() {
+        return Some(candidate.to_string_lossy().to_string());
+    }
     None
 }
+/// Fills in the installation path from the Steam registry key when it is empty.
 pub fn initialize_installation_path(settings: &mut Settings) -> Result<()> {
     if settings.installation_path.is_empty() {
         if let Some(detected) = get_steam_path_from_registry() {
@@ -90,6 +349,23 @@ pub fn initialize_installation_path(settings: &mut Settings) -> Result<()> {
     }
     Ok(())
 }
+/// The `stats` folder KovaaK's writes one CSV per run into.
 pub fn get_stats_directory(settings: &Settings) -> PathBuf {
     PathBuf::from(&settings.installation_path).join("stats")
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn webhook_urls_on_discord_subdomains_are_accepted() {
+        assert!(is_discord_webhook_url("https://discord.com/api/webhooks/123/token"));
+        assert!(is_discord_webhook_url("https://discordapp.com/api/webhooks/123/token"));
+        assert!(is_discord_webhook_url("https://ptb.discord.com/api/webhooks/123/token"));
+        assert!(is_discord_webhook_url("https://canary.discord.com/api/v10/webhooks/123/token"));
+        assert!(!is_discord_webhook_url("http://discord.com/api/webhooks/123/token"));
+        assert!(!is_discord_webhook_url("https://evildiscord.com/api/webhooks/123/token"));
+        assert!(!is_discord_webhook_url("https://discord.com.example.org/api/webhooks/123/token"));
+        assert!(!is_discord_webhook_url("https://discord.com/channels/123"));
+        assert!(!is_discord_webhook_url("https://discord.com/api/webhooks/"));
+    }
 }
\ No newline at end of file
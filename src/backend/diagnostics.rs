@@ -0,0 +1,199 @@
+//! Checks of everything the app depends on, shown in the Diagnostics tab and by `doctor`.
+use crate::backend::config::{Settings, SettingsProblem};
+use crate::backend::kovaaks_utils::{extract_scenario_name, get_session_file_path};
+use crate::backend::stats_filename::list_stats_files;
+use crate::backend::stats_parser::read_run_stats;
+use std::fs;
+use std::path::{Path, PathBuf};
+/// Named pipes or sockets Discord listens on, it takes the first free of these.
+const DISCORD_IPC_SLOTS: u32 = 10;
+/// Settings checked by their own path checks, left out of the "Settings" check.
+const PATH_FIELDS: [&str; 2] = ["installation_path", "steam_path"];
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+impl CheckStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        }
+    }
+}
+/// One line of the diagnostics checklist.
+#[derive(Debug, Clone)]
+pub struct DiagnosticCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+impl DiagnosticCheck {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+/// Runs every check, the online one makes a request to the webapp.
+pub fn run_diagnostics(settings: &Settings) -> Vec<DiagnosticCheck> {
+    let problems = settings.validate();
+    vec![
+        check_settings(&problems),
+        check_game_folder(settings, &problems),
+        check_stats_folder(settings),
+        check_session_file(),
+        check_steam(settings, &problems),
+        check_discord_ipc(),
+        check_online_api(),
+        check_data_files(&crate::backend::get_app_data_dir()),
+    ]
+}
+/// `doctor` output, one `[PASS] name: detail` line per check.
+pub fn format_diagnostics(checks: &[DiagnosticCheck]) -> String {
+    checks
+        .iter()
+        .map(|check| format!("[{}] {}: {}", check.status.label(), check.name, check.detail))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+fn check_settings(problems: &[SettingsProblem]) -> DiagnosticCheck {
+    let messages: Vec<&str> = problems
+        .iter()
+        .filter(|problem| !PATH_FIELDS.contains(&problem.field))
+        .map(|problem| problem.message.as_str())
+        .collect();
+    if messages.is_empty() {
+        DiagnosticCheck::new("Settings", CheckStatus::Pass, "All settings are valid")
+    } else {
+        DiagnosticCheck::new("Settings", CheckStatus::Fail, messages.join(", "))
+    }
+}
+fn check_game_folder(settings: &Settings, problems: &[SettingsProblem]) -> DiagnosticCheck {
+    match problems.iter().find(|problem| problem.field == "installation_path") {
+        Some(problem) => DiagnosticCheck::new("KovaaK's folder", CheckStatus::Fail, problem.message.as_str()),
+        None => DiagnosticCheck::new("KovaaK's folder", CheckStatus::Pass, settings.installation_path.trim()),
+    }
+}
+fn check_stats_folder(settings: &Settings) -> DiagnosticCheck {
+    let stats_dir = crate::backend::get_stats_directory(settings);
+    if let Err(e) = fs::read_dir(&stats_dir) {
+        return DiagnosticCheck::new("Stats folder", CheckStatus::Fail, format!("Can't read {}: {}", stats_dir.display(), e));
+    }
+    let files = list_stats_files(&stats_dir);
+    let Some((newest, _)) = files.iter().max_by_key(|(_, parsed)| parsed.played_at) else {
+        return DiagnosticCheck::new("Stats folder", CheckStatus::Warn, "No stats files yet, play a scenario first");
+    };
+    if read_run_stats(newest).is_none() {
+        return DiagnosticCheck::new(
+            "Stats folder",
+            CheckStatus::Fail,
+            format!("The newest stats file can't be read: {}", newest.display()),
+        );
+    }
+    DiagnosticCheck::new("Stats folder", CheckStatus::Pass, format!("{} stats files", files.len()))
+}
+fn check_session_file() -> DiagnosticCheck {
+    let path = match get_session_file_path() {
+        Ok(path) => path,
+        Err(e) => return DiagnosticCheck::new("session.sav", CheckStatus::Fail, format!("Can't locate it: {}", e)),
+    };
+    if !path.exists() {
+        return DiagnosticCheck::new(
+            "session.sav",
+            CheckStatus::Fail,
+            format!("{} doesn't exist, start KovaaK's once", path.display()),
+        );
+    }
+    match extract_scenario_name(&path) {
+        Ok(name) if name != "Unknown Scenario" => {
+            DiagnosticCheck::new("session.sav", CheckStatus::Pass, format!("Last scenario: {}", name))
+        }
+        Ok(_) => DiagnosticCheck::new("session.sav", CheckStatus::Warn, "Readable, but it names no scenario"),
+        Err(e) => DiagnosticCheck::new("session.sav", CheckStatus::Fail, format!("Can't read {}: {}", path.display(), e)),
+    }
+}
+fn check_steam(settings: &Settings, problems: &[SettingsProblem]) -> DiagnosticCheck {
+    match problems.iter().find(|problem| problem.field == "steam_path") {
+        Some(problem) => DiagnosticCheck::new("Steam", CheckStatus::Fail, problem.message.as_str()),
+        None if settings.open_manually => {
+            DiagnosticCheck::new("Steam", CheckStatus::Pass, "Not needed, KovaaK's is opened manually")
+        }
+        None => DiagnosticCheck::new("Steam", CheckStatus::Pass, settings.steam_path.trim()),
+    }
+}
+fn check_discord_ipc() -> DiagnosticCheck {
+    match discord_ipc_paths().into_iter().find(|path| path.exists()) {
+        Some(path) => DiagnosticCheck::new("Discord", CheckStatus::Pass, format!("Listening on {}", path.display())),
+        None => DiagnosticCheck::new("Discord", CheckStatus::Fail, "Discord is not running or doesn't accept connections"),
+    }
+}
+#[cfg(windows)]
+fn discord_ipc_paths() -> Vec<PathBuf> {
+    (0..DISCORD_IPC_SLOTS)
+        .map(|slot| PathBuf::from(format!(r"\\.\pipe\discord-ipc-{}", slot)))
+        .collect()
+}
+#[cfg(not(windows))]
+fn discord_ipc_paths() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .iter()
+        .filter_map(|var| std::env::var_os(var).map(PathBuf::from))
+        .collect();
+    dirs.push(PathBuf::from("/tmp"));
+    dirs.iter()
+        .flat_map(|dir| (0..DISCORD_IPC_SLOTS).map(move |slot| dir.join(format!("discord-ipc-{}", slot))))
+        .collect()
+}
+#[cfg(feature = "online")]
+fn check_online_api() -> DiagnosticCheck {
+    match crate::backend::OnlineScoreAPI::new().ping() {
+        Ok(()) => DiagnosticCheck::new("Online API", CheckStatus::Pass, "The KovaaK's webapp is reachable"),
+        Err(e) => DiagnosticCheck::new("Online API", CheckStatus::Fail, format!("The KovaaK's webapp is not reachable: {}", e)),
+    }
+}
+#[cfg(not(feature = "online"))]
+fn check_online_api() -> DiagnosticCheck {
+    DiagnosticCheck::new("Online API", CheckStatus::Pass, "Online features are disabled in this build")
+}
+/// Every JSON file in the app data folder must parse, `.bak` files are ones that didn't and were set aside.
+fn check_data_files(app_data_dir: &Path) -> DiagnosticCheck {
+    let Ok(entries) = fs::read_dir(app_data_dir) else {
+        return DiagnosticCheck::new("Data files", CheckStatus::Warn, "No data folder yet");
+    };
+    let mut checked = 0;
+    let mut broken = Vec::new();
+    let mut set_aside = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => {
+                checked += 1;
+                let valid = fs::read_to_string(&path)
+                    .ok()
+                    .is_some_and(|contents| serde_json::from_str::<serde_json::Value>(&contents).is_ok());
+                if !valid {
+                    broken.push(file_name);
+                }
+            }
+            Some("bak") => set_aside.push(file_name),
+            _ => {}
+        }
+    }
+    if !broken.is_empty() {
+        DiagnosticCheck::new("Data files", CheckStatus::Fail, format!("Corrupt: {}", broken.join(", ")))
+    } else if !set_aside.is_empty() {
+        DiagnosticCheck::new(
+            "Data files",
+            CheckStatus::Warn,
+            format!("{} files are fine, earlier corrupt copies were kept as {}", checked, set_aside.join(", ")),
+        )
+    } else {
+        DiagnosticCheck::new("Data files", CheckStatus::Pass, format!("{} files are fine", checked))
+    }
+}
\ No newline at end of file
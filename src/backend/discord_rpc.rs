@@ -1,5 +1,8 @@
+//! Discord IPC connection.
+use crate::backend::presence::PresenceContent;
 use discord_rich_presence::{activity, DiscordIpc, DiscordIpcClient};
 const CLIENT_ID: &str = "1321990331083784202";
+/// Thin wrapper around the Discord IPC client for this app's application id.
 pub struct DiscordRPC {
     client: DiscordIpcClient,
     connected: bool,
@@ -34,34 +37,20 @@ impl DiscordRPC {
     pub fn is_connected(&self) -> bool {
         self.connected
     }
+    /// Publishes presence content built by [`crate::backend::build_presence`].
     pub fn update_presence(
         &mut self,
-        scenario_name: &str,
+        content: &PresenceContent,
         start_time: Option<i64>,
-        highscore: f64,
-        session_highscore: f64,
-        _installation_path: &str,
-        share_code: Option<String>,
     ) -> Result<(), String> {
         if !self.connected {
             return Ok(());
         }
-        if scenario_name.is_empty() || scenario_name == "Unknown Scenario" {
-
-            return Ok(());
-        }
-        let details_text = format!("Playing: {}", scenario_name);
-        let highscore_display = format!("{:.1}", highscore);
-        let state_text = format!("Highscore: {}", highscore_display);
-        let large_text = if session_highscore > 0.0 {
-            let session_display = format!("{:.1}", session_highscore);
-            format!("Session Best: {}", session_display)
-        } else {
-            "No session plays yet".to_string()
-        };
         let mut activity_builder = activity::Activity::new()
-            .details(&details_text)
-            .state(&state_text);
+            .details(&content.details);
+        if let Some(state_text) = &content.state {
+            activity_builder = activity_builder.state(state_text);
+        }
         if let Some(timestamp) = start_time {
             activity_builder = activity_builder.timestamps(
                 activity::Timestamps::new().start(timestamp)
@@ -70,22 +59,28 @@ impl DiscordRPC {
         activity_builder = activity_builder.assets(
             activity::Assets::new()
                 .large_image("kovaak_image")
-                .large_text(&large_text)
-                .small_text(&large_text)
+                .large_text(&content.large_text)
+                .small_text(&content.large_text)
         );
-        let button_url = if let Some(code) = &share_code {
-            format!("steam://run/824270/?action=jump-to-playlist;sharecode={}", code)
-        } else {
-            let encoded_scenario = scenario_name.replace(' ', "%20").replace('&', "%26");
-            format!("steam://run/824270/?action=jump-to-scenario;name={}", encoded_scenario)
-        };
-        let button_label = if share_code.is_some() {
-            "Play Playlist"
-        } else {
-            "Play Scenario"
-        };
-        let button = activity::Button::new(button_label, &button_url);
-        activity_builder = activity_builder.buttons(vec![button]);
+        if let Some(button) = &content.button {
+            activity_builder = activity_builder.buttons(vec![activity::Button::new(&button.label, &button.url)]);
+        }
+        self.client.set_activity(activity_builder)
+            .map_err(|e| format!("Failed to update Discord RPC activity: {}", e))?;
+        Ok(())
+    }
+    /// Idle presence with the text from [`crate::backend::idle_details`].
+    pub fn update_idle_presence(&mut self, details: &str) -> Result<(), String> {
+        if !self.connected {
+            return Ok(());
+        }
+        let activity_builder = activity::Activity::new()
+            .details(details)
+            .assets(
+                activity::Assets::new()
+                    .large_image("kovaak_image")
+                    .large_text("Idle")
+            );
         self.client.set_activity(activity_builder)
             .map_err(|e| format!("Failed to update Discord RPC activity: {}", e))?;
         Ok(())
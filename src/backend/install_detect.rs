@@ -0,0 +1,88 @@
+//! Finding KovaaK's installations for the setup wizard: the registry, every Steam library and Proton prefixes.
+use crate::backend::config::get_steam_root_from_registry;
+use crate::backend::stats_filename::list_stats_files;
+use std::fs;
+use std::path::{Path, PathBuf};
+/// KovaaK's Steam app id, the name of its Proton prefix.
+const KOVAAKS_APP_ID: &str = "824270";
+/// A folder that looks like a KovaaK's installation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstallCandidate {
+    pub path: PathBuf,
+    /// Where it was found, e.g. "Steam library".
+    pub source: &'static str,
+    /// Stats CSVs in its stats folder.
+    pub stats_files: usize,
+}
+/// Every installation found, the one with the most stats files first.
+pub fn find_installations() -> Vec<InstallCandidate> {
+    let mut found: Vec<(PathBuf, &'static str)> = Vec::new();
+    for root in steam_roots() {
+        for library in steam_libraries(&root) {
+            found.push((game_folder(&library), "Steam library"));
+            let prefix = library.join("steamapps").join("compatdata").join(KOVAAKS_APP_ID).join("pfx");
+            if prefix.is_dir() {
+                let windows_steam = prefix.join("drive_c").join("Program Files (x86)").join("Steam");
+                found.push((game_folder(&windows_steam), "Proton prefix"));
+            }
+        }
+    }
+    let mut candidates: Vec<InstallCandidate> = Vec::new();
+    let mut seen: Vec<PathBuf> = Vec::new();
+    for (path, source) in found {
+        if !path.join("stats").is_dir() {
+            continue;
+        }
+        let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if seen.contains(&canonical) {
+            continue;
+        }
+        seen.push(canonical);
+        candidates.push(InstallCandidate {
+            stats_files: count_stats_files(&path),
+            path,
+            source,
+        });
+    }
+    candidates.sort_by(|a, b| b.stats_files.cmp(&a.stats_files));
+    candidates
+}
+/// Stats CSVs in an installation's stats folder, 0 when it has none.
+pub fn count_stats_files(installation_path: &Path) -> usize {
+    list_stats_files(&installation_path.join("stats")).len()
+}
+fn game_folder(steam_library: &Path) -> PathBuf {
+    steam_library
+        .join("steamapps")
+        .join("common")
+        .join("FPSAimTrainer")
+        .join("FPSAimTrainer")
+}
+/// Steam install folders: the registry one plus the usual default locations.
+fn steam_roots() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = get_steam_root_from_registry().into_iter().collect();
+    roots.push(PathBuf::from(r"C:\Program Files (x86)\Steam"));
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join(".steam/steam"));
+        roots.push(home.join(".local/share/Steam"));
+        roots.push(home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+    }
+    roots.retain(|root| root.is_dir());
+    roots
+}
+/// The Steam root itself and every library listed in its `libraryfolders.vdf`.
+fn steam_libraries(steam_root: &Path) -> Vec<PathBuf> {
+    let mut libraries = vec![steam_root.to_path_buf()];
+    let vdf = steam_root.join("steamapps").join("libraryfolders.vdf");
+    if let Ok(contents) = fs::read_to_string(vdf) {
+        for line in contents.lines() {
+            let mut parts = line.split('"').filter(|part| !part.trim().is_empty());
+            if parts.next() == Some("path") {
+                if let Some(path) = parts.next() {
+                    libraries.push(PathBuf::from(path.replace("\\\\", "\\")));
+                }
+            }
+        }
+    }
+    libraries
+}
\ No newline at end of file
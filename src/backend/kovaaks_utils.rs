@@ -1,11 +1,16 @@
-use sysinfo::System;
+//! Reading KovaaK's game files: the running process, session.sav and the
+//! stats folder.
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::io::Read;
 use std::fs::OpenOptions;
 use std::io::Write as IoWrite;
+use crate::backend::config::CountedModes;
+use crate::backend::process_watcher::{ProcessEvent, ProcessWatcher};
+use crate::backend::stats_filename::{list_stats_files, RunMode, StatsFileName};
+use crate::backend::stats_parser::{read_stats_file, RunStats, StatsRead};
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
-fn log_debug(message: &str) {
+pub(crate) fn log_debug(message: &str) {
 
     if let Ok(temp_dir) = std::env::var("TEMP") {
         let log_path = PathBuf::from(temp_dir).join("kovaaks_rpc_debug.log");
@@ -19,25 +24,18 @@ fn log_debug(message: &str) {
         }
     }
 }
+/// Watcher behind [`is_kovaaks_running`], kept so repeated checks only look at the known PID.
+static RUNNING_CHECK_WATCHER: parking_lot::Mutex<Option<ProcessWatcher>> = parking_lot::Mutex::new(None);
+/// Check for a running game process, see [`ProcessWatcher`] for polling with events.
 pub fn is_kovaaks_running() -> bool {
-    let mut system = System::new_all();
-    system.refresh_processes();
-    for (_pid, process) in system.processes() {
-
-        let name = process.name();
-        let process_name = name.to_lowercase();
-        if process_name.contains("fpsaimtrainer") {
-            if process_name.contains("discord") || process_name.contains("rpc") {
-                log_debug(&format!("[DEBUG] Skipping RPC app itself: {}", name));
-                continue;
-            }
-            log_debug(&format!("[DEBUG] Kovaaks process detected: {}", name));
-            return true;
-        }
+    let mut watcher = RUNNING_CHECK_WATCHER.lock();
+    let watcher = watcher.get_or_insert_with(ProcessWatcher::new);
+    if let Some(ProcessEvent::Exited { .. }) = watcher.poll() {
+        watcher.poll();
     }
-    log_debug("[DEBUG] Kovaaks not detected");
-    false
+    watcher.is_running()
 }
+/// Pulls the last scenario name out of a copy of session.sav.
 pub fn extract_scenario_name(file_path: &Path) -> Result<String> {
     let mut file = fs::File::open(file_path)?;
     let mut data = Vec::new();
@@ -72,13 +70,19 @@ fn find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize> {
         .windows(needle.len())
         .position(|window| window == needle)
 }
-pub fn get_current_scenario() -> Result<String> {
-    let temp_dir = std::env::var("LOCALAPPDATA")?;
-    let source_path = PathBuf::from(&temp_dir)
+/// Location of the game's session.sav under `%LOCALAPPDATA%`.
+pub fn get_session_file_path() -> Result<PathBuf> {
+    let local_app_data = std::env::var("LOCALAPPDATA")?;
+    Ok(PathBuf::from(local_app_data)
         .join("FPSAimTrainer")
         .join("Saved")
         .join("SaveGames")
-        .join("session.sav");
+        .join("session.sav"))
+}
+/// The scenario currently loaded in the game, or `"Unknown Scenario"`.
+pub fn get_current_scenario() -> Result<String> {
+    let temp_dir = std::env::var("LOCALAPPDATA")?;
+    let source_path = get_session_file_path()?;
     if source_path.exists() {
         let temp_file = PathBuf::from(&temp_dir)
             .join("Temp")
@@ -91,161 +95,179 @@ pub fn get_current_scenario() -> Result<String> {
         Ok("Unknown Scenario".to_string())
     }
 }
-pub fn find_initial_scores(scenario_name: &str, stats_directory: &Path) -> Result<(f64, Vec<String>)> {
-    let mut highscore: f64 = 0.0;
-    let mut checked_files = Vec::new();
-    if let Ok(entries) = fs::read_dir(stats_directory) {
-        for entry in entries.flatten() {
-            let file_name = entry.file_name();
-            let file_name_str = file_name.to_string_lossy();
-            if file_name_str.starts_with(&format!("{} - ", scenario_name)) && file_name_str.ends_with(".csv") {
-                let file_path = entry.path();
-                if let Ok(content) = fs::read_to_string(&file_path) {
-                    for line in content.lines() {
-                        if line.contains("Score:,") {
-                            if let Some(score_str) = line.split(',').nth(1) {
-                                if let Ok(score) = score_str.parse::<f64>() {
-                                    highscore = highscore.max(score);
-                                }
-                            }
-                        }
-                    }
-                }
-                checked_files.push(file_name_str.to_string());
+/// Best scores of a scenario in the stats folder per mode, and the files that were read.
+#[derive(Debug, Clone, Default)]
+pub struct InitialScores {
+    pub challenge_best: f64,
+    pub freeplay_best: f64,
+    /// Files read or given up on, files still being written are left out.
+    pub checked_files: Vec<String>,
+    /// Names of stats files that couldn't be read.
+    pub unreadable_files: Vec<String>,
+}
+impl InitialScores {
+    pub fn mode_best(&self, mode: RunMode) -> f64 {
+        match mode {
+            RunMode::Challenge => self.challenge_best,
+            RunMode::Freeplay => self.freeplay_best,
+        }
+    }
+    /// Best score over the counted modes.
+    pub fn best(&self, counted: CountedModes) -> f64 {
+        RunMode::ALL
+            .into_iter()
+            .filter(|mode| counted.counts(*mode))
+            .map(|mode| self.mode_best(mode))
+            .fold(0.0, f64::max)
+    }
+}
+pub fn find_initial_scores(scenario_name: &str, stats_directory: &Path) -> Result<InitialScores> {
+    let mut scores = InitialScores::default();
+    for (file_path, parsed) in list_stats_files(stats_directory) {
+        if !parsed.is_scenario(scenario_name) {
+            continue;
+        }
+        let file_name = file_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        match read_stats_file(&file_path) {
+            StatsRead::Run(run) => {
+                let best = match parsed.mode {
+                    RunMode::Challenge => &mut scores.challenge_best,
+                    RunMode::Freeplay => &mut scores.freeplay_best,
+                };
+                *best = best.max(run.score);
             }
+            StatsRead::Incomplete => continue,
+            StatsRead::Unreadable => scores.unreadable_files.push(file_name.clone()),
         }
+        scores.checked_files.push(file_name);
+    }
+    scores.challenge_best = (scores.challenge_best * 10.0).round() / 10.0;
+    scores.freeplay_best = (scores.freeplay_best * 10.0).round() / 10.0;
+    Ok(scores)
+}
+/// A run read from a stats file.
+#[derive(Debug, Clone)]
+pub struct FoundRun {
+    pub played_at: Option<std::time::SystemTime>,
+    pub mode: RunMode,
+    pub stats: RunStats,
+}
+/// Runs found in stats files that weren't checked yet.
+#[derive(Debug, Clone, Default)]
+pub struct NewRuns {
+    /// Best score over the counted modes, 0 when only other modes were played.
+    pub best_score: f64,
+    pub found_new: bool,
+    pub best_played_at: Option<std::time::SystemTime>,
+    pub latest_run: Option<RunStats>,
+    pub runs: Vec<FoundRun>,
+    /// Names of new stats files that couldn't be read, files still being written aren't listed.
+    pub unreadable_files: Vec<String>,
+}
+impl NewRuns {
+    /// Best new run of `mode`.
+    pub fn best_of(&self, mode: RunMode) -> Option<&FoundRun> {
+        self.runs
+            .iter()
+            .filter(|run| run.mode == mode)
+            .max_by(|a, b| a.stats.score.total_cmp(&b.stats.score))
+    }
+    pub fn latest_mode(&self) -> Option<RunMode> {
+        self.runs.last().map(|run| run.mode)
     }
-    Ok(((highscore * 10.0).round() / 10.0, checked_files))
 }
+/// Reads stats files not in `checked_files`, runs are ordered oldest first.
 pub fn find_fight_time_and_score(
     scenario_name: &str,
     stats_directory: &Path,
     checked_files: &[String],
-) -> Result<(f64, bool, Option<std::time::SystemTime>)> {
+    counted: CountedModes,
+) -> Result<NewRuns> {
     let mut max_score: f64 = 0.0;
     let mut found_new_score = false;
     let mut newest_file_time: Option<std::time::SystemTime> = None;
-    if let Ok(entries) = fs::read_dir(stats_directory) {
-        for entry in entries.flatten() {
-            let file_name = entry.file_name();
-            let file_name_str = file_name.to_string_lossy();
-            if file_name_str.starts_with(&format!("{} - ", scenario_name))
-                && !checked_files.contains(&file_name_str.to_string()) {
-                let file_path = entry.path();
-                let file_time = if let Ok(metadata) = fs::metadata(&file_path) {
-                    metadata.created().or_else(|_| metadata.modified()).ok()
-                } else {
-                    None
-                };
-                if let Ok(content) = fs::read_to_string(&file_path) {
-                    for line in content.lines() {
-                        if line.contains("Score:,") {
-                            if let Some(score_str) = line.split(',').nth(1) {
-                                if let Ok(score) = score_str.parse::<f64>() {
-                                    if score > max_score {
-                                        max_score = score;
-                                        newest_file_time = file_time;
-                                    }
-                                    found_new_score = true;
-                                }
-                            }
-                        }
-                    }
+    let mut runs: Vec<FoundRun> = Vec::new();
+    let mut unreadable_files = Vec::new();
+    for (file_path, parsed) in list_stats_files(stats_directory) {
+        if !parsed.is_scenario(scenario_name) {
+            continue;
+        }
+        let file_name = file_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if checked_files.contains(&file_name) {
+            continue;
+        }
+        let file_time = parsed.played_at_system_time();
+        match read_stats_file(&file_path) {
+            StatsRead::Run(run) => {
+                if counted.counts(parsed.mode) && run.score > max_score {
+                    max_score = run.score;
+                    newest_file_time = file_time;
                 }
+                found_new_score = true;
+                runs.push(FoundRun {
+                    played_at: file_time,
+                    mode: parsed.mode,
+                    stats: run,
+                });
             }
+            StatsRead::Incomplete => {}
+            StatsRead::Unreadable => unreadable_files.push(file_name),
         }
     }
-    Ok(((max_score * 10.0).round() / 10.0, found_new_score, newest_file_time))
+    runs.sort_by_key(|run| run.played_at);
+    Ok(NewRuns {
+        best_score: (max_score * 10.0).round() / 10.0,
+        found_new: found_new_score,
+        best_played_at: newest_file_time,
+        latest_run: runs.last().map(|run| run.stats.clone()),
+        runs,
+        unreadable_files,
+    })
 }
 #[allow(dead_code)]
 pub fn get_last_played_time(scenario_name: &str, stats_directory: &Path) -> Option<std::time::SystemTime> {
-    let mut newest_time: Option<std::time::SystemTime> = None;
-    if let Ok(entries) = fs::read_dir(stats_directory) {
-        for entry in entries.flatten() {
-            let file_name = entry.file_name();
-            let file_name_str = file_name.to_string_lossy();
-            if file_name_str.starts_with(&format!("{} - ", scenario_name)) && file_name_str.ends_with(".csv") {
-                let file_path = entry.path();
-                if let Ok(metadata) = fs::metadata(&file_path) {
-                    let file_time = metadata.created().or_else(|_| metadata.modified()).ok();
-                    if let Some(time) = file_time {
-                        newest_time = Some(newest_time.map_or(time, |current| current.max(time)));
-                    }
-                }
-            }
-        }
-    }
-    newest_time
+    list_stats_files(stats_directory)
+        .into_iter()
+        .filter(|(_, parsed)| parsed.is_scenario(scenario_name))
+        .filter_map(|(_, parsed)| parsed.played_at_system_time())
+        .max()
 }
-pub fn scan_all_stats_folder(stats_dir: &Path) -> Result<std::collections::HashMap<String, (f64, Option<std::time::SystemTime>)>> {
+/// Best scores found by [`scan_stats_folder_since`].
+#[derive(Debug, Clone, Default)]
+pub struct FolderScan {
+    pub scores: std::collections::HashMap<(String, RunMode), (f64, Option<std::time::SystemTime>)>,
+    /// Names of stats files that couldn't be read.
+    pub unreadable_files: Vec<String>,
+}
+/// Best score and last played time of every scenario and mode in the stats folder.
+pub fn scan_all_stats_folder(stats_dir: &Path) -> Result<FolderScan> {
     scan_stats_folder_since(stats_dir, None)
 }
-pub fn scan_stats_folder_since(stats_dir: &Path, since_timestamp: Option<u64>) -> Result<std::collections::HashMap<String, (f64, Option<std::time::SystemTime>)>> {
-    use std::collections::HashMap;
-    use std::time::UNIX_EPOCH;
-    let mut scenario_scores: HashMap<String, (f64, Option<std::time::SystemTime>)> = HashMap::new();
+/// Like [`scan_all_stats_folder`], only reading runs played after `since_timestamp`.
+pub fn scan_stats_folder_since(stats_dir: &Path, since_timestamp: Option<u64>) -> Result<FolderScan> {
+    let mut scan = FolderScan::default();
     if !stats_dir.exists() {
 
-        return Ok(scenario_scores);
-    }
-    if let Some(_ts) = since_timestamp {
+        return Ok(scan);
     }
-    let entries = match fs::read_dir(stats_dir) {
-        Ok(entries) => entries,
-        Err(_) => return Ok(scenario_scores),
-    };
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.extension().and_then(|s| s.to_str()) != Some("csv") {
-            continue;
-        }
-        if let Some(since) = since_timestamp {
-            if let Ok(metadata) = fs::metadata(&path) {
-                if let Ok(modified) = metadata.modified() {
-                    if let Ok(duration) = modified.duration_since(UNIX_EPOCH) {
-                        let file_timestamp = duration.as_secs();
-                        if file_timestamp <= since {
-                            continue;
-                        }
-                    }
-                }
+    for (path, parsed) in list_stats_files(stats_dir) {
+        if let (Some(since), Some(played_at)) = (since_timestamp, parsed.played_at_unix()) {
+            if played_at <= since {
+                continue;
             }
         }
-        let file_name = match path.file_name().and_then(|n| n.to_str()) {
-            Some(name) => name,
-            None => continue,
-        };
-        let name_without_ext = file_name.trim_end_matches(".csv");
-        let scenario_name = if let Some(pos) = name_without_ext.rfind(" - ") {
-            name_without_ext[..pos].to_string()
-        } else {
-            name_without_ext.to_string()
-        };
-        if scenario_name.is_empty() {
-            continue;
-        }
-        let score = match fs::read_to_string(&path) {
-            Ok(content) => {
-                let mut found_score = 0.0;
-                for line in content.lines() {
-                    if line.contains("Score:,") {
-                        if let Some(score_str) = line.split(',').nth(1) {
-                            if let Ok(score) = score_str.parse::<f64>() {
-                                found_score = score;
-                                break;
-                            }
-                        }
-                    }
-                }
-                found_score
+        let score = match read_stats_file(&path) {
+            StatsRead::Run(run) => run.score,
+            StatsRead::Incomplete => continue,
+            StatsRead::Unreadable => {
+                scan.unreadable_files.push(path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default());
+                continue;
             }
-            Err(_) => continue,
         };
-        let last_played = fs::metadata(&path)
-            .ok()
-            .and_then(|m| m.modified().ok());
-        scenario_scores
-            .entry(scenario_name.clone())
+        let last_played = parsed.played_at_system_time();
+        let StatsFileName { scenario, mode, .. } = parsed;
+        scan.scores
+            .entry((scenario, mode))
             .and_modify(|(existing_score, existing_time)| {
                 if score > *existing_score {
                     *existing_score = score;
@@ -254,8 +276,9 @@ pub fn scan_stats_folder_since(stats_dir: &Path, since_timestamp: Option<u64>) -
             })
             .or_insert((score, last_played));
     }
-    Ok(scenario_scores)
+    Ok(scan)
 }
+/// Share code of the playlist in progress, if any.
 pub fn get_playlist_share_code(installation_path: &str) -> Option<String> {
     let playlist_file = PathBuf::from(installation_path)
         .join("Saved")
@@ -270,4 +293,42 @@ pub fn get_playlist_share_code(installation_path: &str) -> Option<String> {
         }
     }
     None
+}
+/// Name and position of the playlist in progress.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaylistProgress {
+    pub name: Option<String>,
+    pub position: Option<usize>,
+    pub total: usize,
+}
+/// Reads the playlist in progress, `position` is where `current_scenario` sits in it (1-based).
+pub fn get_playlist_progress(installation_path: &str, current_scenario: &str) -> Option<PlaylistProgress> {
+    let playlist_file = PathBuf::from(installation_path)
+        .join("Saved")
+        .join("SaveGames")
+        .join("PlaylistInProgress.json");
+    let content = fs::read_to_string(playlist_file).ok()?;
+    let playlist: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let name = playlist
+        .get("playlistName")
+        .and_then(|value| value.as_str())
+        .map(str::to_string);
+    let scenarios: Vec<&str> = playlist
+        .get("scenarioList")
+        .and_then(|value| value.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|entry| entry.get("scenario_name").and_then(|value| value.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let position = scenarios
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(current_scenario))
+        .map(|index| index + 1);
+    Some(PlaylistProgress {
+        name,
+        position,
+        total: scenarios.len(),
+    })
 }
\ No newline at end of file
@@ -1,24 +1,125 @@
+//! The local highscore store (`local_scores.json`).
+use crate::backend::config::{CountedModes, ScoreDisplay};
+use crate::backend::scenario_key::{normalize_scenario_name, scenario_key};
+use crate::backend::stats_filename::RunMode;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
-pub fn normalize_scenario_name(name: &str) -> String {
-    name.trim_end_matches(" - Challenge").to_string()
-}
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum ScoreSource {
     Local,
     Online,
 }
+/// Version of the store where `highscore` only holds local runs.
+const SPLIT_SCORES_VERSION: u32 = 2;
+/// Stored local and online bests of one scenario.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScenarioScore {
     pub scenario_name: String,
+    /// Best local run over all modes.
     pub highscore: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_played: Option<u64>,
+    /// Where `highscore` came from in files before version 2, always `Local` since.
     pub source: ScoreSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highscore_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub online_highscore: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub online_synced_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goal: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub challenge_best: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freeplay_best: Option<f64>,
+}
+impl ScenarioScore {
+    fn empty(scenario_name: String) -> Self {
+        Self {
+            scenario_name,
+            highscore: 0.0,
+            last_played: None,
+            source: ScoreSource::Local,
+            highscore_at: None,
+            online_highscore: None,
+            online_synced_at: None,
+            goal: None,
+            challenge_best: None,
+            freeplay_best: None,
+        }
+    }
+    pub fn mode_best(&self, mode: RunMode) -> Option<f64> {
+        match mode {
+            RunMode::Challenge => self.challenge_best,
+            RunMode::Freeplay => self.freeplay_best,
+        }
+    }
+    /// Raises the best of `mode` to `score` if it is higher.
+    pub fn record_mode_best(&mut self, mode: RunMode, score: f64) {
+        let best = match mode {
+            RunMode::Challenge => &mut self.challenge_best,
+            RunMode::Freeplay => &mut self.freeplay_best,
+        };
+        match best {
+            Some(best) if *best >= score => {}
+            _ => *best = Some(score),
+        }
+    }
+    /// Highscore over the counted modes, the overall highscore while the modes of the scores aren't known.
+    pub fn counted_highscore(&self, counted: CountedModes) -> f64 {
+        if self.challenge_best.is_none() && self.freeplay_best.is_none() {
+            return self.highscore;
+        }
+        match counted {
+            CountedModes::Both => self.highscore,
+            CountedModes::ChallengeOnly => self.challenge_best.unwrap_or(0.0),
+            CountedModes::FreeplayOnly => self.freeplay_best.unwrap_or(0.0),
+        }
+    }
+    /// Highscore to show for the display setting, falling back to the local one without an online score.
+    pub fn display_highscore(&self, counted: CountedModes, display: ScoreDisplay) -> f64 {
+        display_score(self.counted_highscore(counted), self.online_highscore, display)
+    }
+    /// Whether a local and an online highscore are both known and differ.
+    pub fn disagrees(&self) -> bool {
+        match self.online_highscore {
+            Some(online) => self.highscore > 0.0 && (self.highscore - online).abs() >= 0.05,
+            None => false,
+        }
+    }
+    /// [`Self::display_highscore`] as a fraction of the goal in percent, `None` without a goal.
+    pub fn goal_percent(&self, counted: CountedModes, display: ScoreDisplay) -> Option<f64> {
+        let goal = self.goal.filter(|goal| *goal > 0.0)?;
+        Some(self.display_highscore(counted, display) / goal * 100.0)
+    }
+}
+/// The single score standing for a highscore under the display setting, what goals are measured against.
+pub fn display_score(local: f64, online: Option<f64>, display: ScoreDisplay) -> f64 {
+    match (display, online) {
+        (ScoreDisplay::Local, _) | (_, None) => local,
+        (ScoreDisplay::Online, Some(online)) => online,
+        (ScoreDisplay::Max | ScoreDisplay::Both, Some(online)) => local.max(online),
+    }
+}
+/// The highscore as shown on Discord and in the app, "850.0 (900.0 online)" when showing both.
+pub fn format_highscore(local: f64, online: Option<f64>, display: ScoreDisplay) -> String {
+    match (display, online) {
+        (ScoreDisplay::Local, _) | (_, None) => format!("{:.1}", local),
+        (ScoreDisplay::Online, Some(online)) => format!("{:.1}", online),
+        (ScoreDisplay::Max, Some(online)) => format!("{:.1}", local.max(online)),
+        (ScoreDisplay::Both, Some(online)) => format!("{:.1} ({:.1} online)", local, online),
+    }
+}
+/// "850 / 1000 (85%)"
+pub fn format_goal_progress(score: f64, goal: f64) -> String {
+    let percent = if goal > 0.0 { score / goal * 100.0 } else { 0.0 };
+    format!("{:.0} / {:.0} ({:.0}%)", score, goal, percent)
 }
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LocalScoresFile {
@@ -28,13 +129,16 @@ pub struct LocalScoresFile {
 impl Default for LocalScoresFile {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: SPLIT_SCORES_VERSION,
             scores: HashMap::new(),
         }
     }
 }
+/// Reads and writes the highscore store, every call goes to disk.
 pub struct LocalScoresManager {
     file_path: PathBuf,
+    /// Held from load to save, so concurrent writers neither lose updates nor share the `.tmp` file.
+    file_lock: Mutex<()>,
 }
 #[allow(dead_code)]
 impl LocalScoresManager {
@@ -42,9 +146,20 @@ impl LocalScoresManager {
         let app_data_dir = crate::backend::get_app_data_dir();
         let _ = fs::create_dir_all(&app_data_dir);
         let file_path = app_data_dir.join("local_scores.json");
-        Ok(Self { file_path })
+        Ok(Self {
+            file_path,
+            file_lock: Mutex::new(()),
+        })
     }
     pub fn load(&self) -> Result<LocalScoresFile> {
+        let _guard = self.file_lock.lock();
+        self.read_file()
+    }
+    pub fn save(&self, data: &LocalScoresFile) -> Result<()> {
+        let _guard = self.file_lock.lock();
+        self.write_file(data)
+    }
+    fn read_file(&self) -> Result<LocalScoresFile> {
         if !self.file_path.exists() {
 
             return Ok(LocalScoresFile::default());
@@ -58,27 +173,47 @@ impl LocalScoresManager {
                     Ok(data) => {
                         let mut normalized_scores = HashMap::new();
                         let mut migration_count = 0;
+                        let split_online = data.version < SPLIT_SCORES_VERSION;
                         for (old_name, mut score) in data.scores.into_iter() {
-                            let normalized_name = normalize_scenario_name(&old_name);
-                            if normalized_name != old_name {
+                            if split_online {
+                                split_online_highscore(&mut score);
                                 migration_count += 1;
-                                score.scenario_name = normalized_name.clone();
                             }
-                            if let Some(existing) = normalized_scores.get_mut(&normalized_name) {
+                            let display_name = normalize_scenario_name(&score.scenario_name);
+                            let key = scenario_key(&display_name);
+                            if key != old_name || display_name != score.scenario_name {
+                                migration_count += 1;
+                                score.scenario_name = display_name;
+                            }
+                            if let Some(existing) = normalized_scores.get_mut(&key) {
                                 let existing_score: &mut ScenarioScore = existing;
-                                if score.highscore > existing_score.highscore {
-                                    *existing_score = score;
+                                let goal = existing_score.goal.or(score.goal);
+                                let online = match (existing_score.online_highscore, score.online_highscore) {
+                                    (Some(a), Some(b)) => Some(a.max(b)),
+                                    (a, b) => a.or(b),
+                                };
+                                let other = if score.highscore > existing_score.highscore {
+                                    std::mem::replace(existing_score, score)
+                                } else {
+                                    score
+                                };
+                                existing_score.goal = goal;
+                                existing_score.online_highscore = online;
+                                for mode in RunMode::ALL {
+                                    if let Some(best) = other.mode_best(mode) {
+                                        existing_score.record_mode_best(mode, best);
+                                    }
                                 }
                             } else {
-                                normalized_scores.insert(normalized_name, score);
+                                normalized_scores.insert(key, score);
                             }
                         }
                         let migrated_data = LocalScoresFile {
-                            version: data.version,
+                            version: data.version.max(SPLIT_SCORES_VERSION),
                             scores: normalized_scores,
                         };
                         if migration_count > 0 {
-                            let _ = self.save(&migrated_data);
+                            let _ = self.write_file(&migrated_data);
                         }
                         Ok(migrated_data)
                     }
@@ -93,7 +228,7 @@ impl LocalScoresManager {
             Err(_) => Ok(LocalScoresFile::default()),
         }
     }
-    pub fn save(&self, data: &LocalScoresFile) -> Result<()> {
+    fn write_file(&self, data: &LocalScoresFile) -> Result<()> {
         let json = serde_json::to_string_pretty(data)?;
         let mut tmp_path = self.file_path.clone();
         tmp_path.set_extension("tmp");
@@ -103,125 +238,187 @@ impl LocalScoresManager {
     }
     pub fn get_score(&self, scenario_name: &str) -> Result<Option<ScenarioScore>> {
         let data = self.load()?;
-        Ok(data.scores.get(scenario_name).cloned())
+        Ok(data.scores.get(&scenario_key(scenario_name)).cloned())
     }
+    /// Records a score, returning `true` when it is a new local highscore over all modes or a changed online one.
     pub fn update_score(
         &self,
         scenario_name: &str,
         new_score: f64,
         last_played: Option<SystemTime>,
         source: ScoreSource,
+        mode: Option<RunMode>,
     ) -> Result<bool> {
         let normalized_name = normalize_scenario_name(scenario_name);
-        let mut data = self.load()?;
+        let _guard = self.file_lock.lock();
+        let mut data = self.read_file()?;
         let last_played_timestamp = last_played.and_then(|t| {
             t.duration_since(SystemTime::UNIX_EPOCH)
                 .ok()
                 .map(|d| d.as_secs())
         });
-        let mut is_new_highscore = false;
-        if let Some(existing) = data.scores.get_mut(&normalized_name) {
-            if new_score > existing.highscore {
-                existing.highscore = new_score;
-                existing.last_played = last_played_timestamp;
-                existing.source = source;
-                is_new_highscore = true;
-            } else {
+        let existing = data
+            .scores
+            .entry(scenario_key(&normalized_name))
+            .or_insert_with(|| ScenarioScore::empty(normalized_name));
+        let changed = match source {
+            ScoreSource::Local => {
+                if let Some(mode) = mode {
+                    existing.record_mode_best(mode, new_score);
+                }
                 if last_played_timestamp.is_some() {
                     existing.last_played = last_played_timestamp;
                 }
+                if new_score > existing.highscore {
+                    existing.highscore = new_score;
+                    existing.highscore_at = last_played_timestamp.or_else(|| Some(unix_now()));
+                    true
+                } else {
+                    false
+                }
             }
-        } else {
-            data.scores.insert(
-                normalized_name.clone(),
-                ScenarioScore {
-                    scenario_name: normalized_name,
-                    highscore: new_score,
-                    last_played: last_played_timestamp,
-                    source,
-                },
-            );
-            is_new_highscore = true;
-        }
-        self.save(&data)?;
-        Ok(is_new_highscore)
+            ScoreSource::Online => set_online_highscore(existing, new_score),
+        };
+        self.write_file(&data)?;
+        Ok(changed)
     }
-    pub fn populate_from_stats_folder(&self, stats_scores: HashMap<String, (f64, Option<SystemTime>)>) -> Result<usize> {
-        let mut data = self.load()?;
+    /// Merges the result of a stats folder scan into the store.
+    pub fn populate_from_stats_folder(&self, stats_scores: HashMap<(String, RunMode), (f64, Option<SystemTime>)>) -> Result<usize> {
+        let _guard = self.file_lock.lock();
+        let mut data = self.read_file()?;
         let mut updated_count = 0;
-        for (scenario_name, (highscore, last_played)) in stats_scores {
+        for ((scenario_name, mode), (highscore, last_played)) in stats_scores {
             let normalized_name = normalize_scenario_name(&scenario_name);
             let last_played_timestamp = last_played.and_then(|t| {
                 t.duration_since(SystemTime::UNIX_EPOCH)
                     .ok()
                     .map(|d| d.as_secs())
             });
-            if let Some(existing) = data.scores.get_mut(&normalized_name) {
-                if highscore > existing.highscore {
-                    existing.highscore = highscore;
-                    existing.last_played = last_played_timestamp;
-                    existing.source = ScoreSource::Local;
-                    updated_count += 1;
-                } else {
-                    if last_played_timestamp.is_some() && existing.last_played != last_played_timestamp {
-                        existing.last_played = last_played_timestamp;
-                    }
-                }
-            } else {
-
-                data.scores.insert(
-                    normalized_name.clone(),
-                    ScenarioScore {
-                        scenario_name: normalized_name,
-                        highscore,
-                        last_played: last_played_timestamp,
-                        source: ScoreSource::Local,
-                    },
-                );
+            let existing = data
+                .scores
+                .entry(scenario_key(&normalized_name))
+                .or_insert_with(|| ScenarioScore::empty(normalized_name));
+            existing.record_mode_best(mode, highscore);
+            if highscore > existing.highscore {
+                existing.highscore = highscore;
+                existing.highscore_at = last_played_timestamp;
                 updated_count += 1;
             }
+            if last_played_timestamp.is_some() && existing.last_played < last_played_timestamp {
+                existing.last_played = last_played_timestamp;
+            }
         }
-        self.save(&data)?;
+        self.write_file(&data)?;
 
         Ok(updated_count)
     }
+    /// Stores highscores fetched from the webapp next to the local ones.
     pub fn merge_online_scores(&self, online_scores: HashMap<String, f64>) -> Result<usize> {
-        let mut data = self.load()?;
+        let _guard = self.file_lock.lock();
+        let mut data = self.read_file()?;
         let mut updated_count = 0;
         for (scenario_name, online_score) in online_scores {
-            if let Some(existing) = data.scores.get_mut(&scenario_name) {
-                if online_score > existing.highscore {
-                    existing.highscore = online_score;
-                    existing.source = ScoreSource::Online;
-                    updated_count += 1;
-                }
-            } else {
-
-                data.scores.insert(
-                    scenario_name.clone(),
-                    ScenarioScore {
-                        scenario_name,
-                        highscore: online_score,
-                        last_played: None,
-                        source: ScoreSource::Online,
-                    },
-                );
+            let normalized_name = normalize_scenario_name(&scenario_name);
+            let existing = data
+                .scores
+                .entry(scenario_key(&normalized_name))
+                .or_insert_with(|| ScenarioScore::empty(normalized_name));
+            if set_online_highscore(existing, online_score) {
                 updated_count += 1;
             }
         }
-        self.save(&data)?;
+        self.write_file(&data)?;
 
         Ok(updated_count)
     }
+    /// Sets or clears the goal of a scenario, adding an entry for scenarios not played yet.
+    pub fn set_goal(&self, scenario_name: &str, goal: Option<f64>) -> Result<()> {
+        let _guard = self.file_lock.lock();
+        let mut data = self.read_file()?;
+        set_goal_in(&mut data, scenario_name, goal);
+        self.write_file(&data)
+    }
+    /// Imports goals from a CSV of `scenario,goal` rows, returning how many were set.
+    pub fn import_goals_csv(&self, csv_path: &Path) -> Result<usize> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_path(csv_path)?;
+        let _guard = self.file_lock.lock();
+        let mut data = self.read_file()?;
+        let mut imported = 0;
+        for record in reader.records() {
+            let record = record?;
+            let (Some(name), Some(goal)) = (record.get(0), record.get(1)) else {
+                continue;
+            };
+            let Ok(goal) = goal.parse::<f64>() else {
+                continue;
+            };
+            if name.is_empty() || goal <= 0.0 {
+                continue;
+            }
+            set_goal_in(&mut data, name, Some(goal));
+            imported += 1;
+        }
+        if imported > 0 {
+            self.write_file(&data)?;
+        }
+        Ok(imported)
+    }
+    /// Every stored scenario keyed by [`scenario_key`].
     pub fn get_all_scores(&self) -> Result<HashMap<String, ScenarioScore>> {
         let data = self.load()?;
         Ok(data.scores)
     }
     pub fn was_played_locally(&self, scenario_name: &str) -> Result<bool> {
         let data = self.load()?;
-        Ok(data.scores.get(scenario_name).and_then(|s| s.last_played).is_some())
+        Ok(data
+            .scores
+            .get(&scenario_key(scenario_name))
+            .and_then(|s| s.last_played)
+            .is_some())
     }
     pub fn get_path(&self) -> &PathBuf {
         &self.file_path
     }
+}
+fn set_goal_in(data: &mut LocalScoresFile, scenario_name: &str, goal: Option<f64>) {
+    let normalized_name = normalize_scenario_name(scenario_name);
+    let key = scenario_key(&normalized_name);
+    if let Some(existing) = data.scores.get_mut(&key) {
+        existing.goal = goal;
+    } else if goal.is_some() {
+        let mut score = ScenarioScore::empty(normalized_name);
+        score.goal = goal;
+        data.scores.insert(key, score);
+    }
+}
+fn set_online_highscore(score: &mut ScenarioScore, online: f64) -> bool {
+    score.online_synced_at = Some(unix_now());
+    if score.online_highscore == Some(online) {
+        return false;
+    }
+    score.online_highscore = Some(online);
+    true
+}
+/// Moves a highscore that came from the webapp out of the local highscore.
+fn split_online_highscore(score: &mut ScenarioScore) {
+    if score.source != ScoreSource::Online {
+        return;
+    }
+    score.online_highscore = Some(score.highscore);
+    score.highscore = score
+        .challenge_best
+        .into_iter()
+        .chain(score.freeplay_best)
+        .fold(0.0, f64::max);
+    score.source = ScoreSource::Local;
+}
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
\ No newline at end of file
@@ -1,21 +1,88 @@
+//! Game file access, stats parsing, score storage, the webapp client and
+//! presence building, usable without the GUI.
 pub mod autostart;
+pub mod calendar;
+pub mod catch_up;
 pub mod config;
+pub mod diagnostics;
 pub mod discord_rpc;
+pub mod install_detect;
 pub mod kovaaks_utils;
 pub mod local_scores;
+pub mod obs_output;
+#[cfg(feature = "online")]
 pub mod online_api;
+pub mod presence;
+pub mod privacy;
+pub mod process_watcher;
+pub mod profiles;
+pub mod run_history;
 pub mod scenario_cache;
+pub mod scenario_key;
+pub mod sensitivity;
+pub mod session_summary;
+pub mod stats_filename;
+pub mod stats_index;
+pub mod stats_parser;
+pub mod trend;
+#[cfg(feature = "online")]
+pub mod webhook;
 pub use autostart::*;
+pub use calendar::{DayStats, TrainingCalendar};
+pub use catch_up::{CatchUpManager, CatchUpMarker, CatchUpReport};
 pub use config::*;
+pub use diagnostics::{format_diagnostics, run_diagnostics, CheckStatus, DiagnosticCheck};
 pub use discord_rpc::DiscordRPC;
+pub use install_detect::{count_stats_files, find_installations, InstallCandidate};
 pub use kovaaks_utils::*;
 pub use local_scores::*;
+#[cfg(feature = "online")]
 pub use online_api::OnlineScoreAPI;
-pub use scenario_cache::ScenarioValidationCache;
+pub use obs_output::{ObsOutput, ObsSnapshot};
+pub use presence::{
+    build_presence, idle_details, render_template, PresenceButton, PresenceContent, PresenceInput,
+    PRESENCE_PLACEHOLDERS,
+};
+pub use privacy::{glob_matches, is_scenario_listed_visible, parse_pattern_list};
+pub use process_watcher::{ProcessEvent, ProcessWatcher};
+pub use profiles::{
+    active_profile, load_active_profile, profile_data_dir, set_active_profile, ProfileManager,
+    ProfilesFile, DEFAULT_PROFILE,
+};
+pub use run_history::{sensitivity_breakdown, RunHistoryManager, RunRecord, SensitivityBucket};
+pub use scenario_cache::{ScenarioValidationCache, ValidationEntry};
+pub use scenario_key::{
+    format_alias_list, normalize_scenario_name, parse_alias_list, scenario_key,
+    set_scenario_aliases,
+};
+pub use sensitivity::cm_per_360;
+pub use session_summary::{
+    format_duration, format_timestamp, ScenarioSummary, SessionLogManager, SessionSummary,
+};
+pub use stats_filename::{list_stats_files, RunMode, StatsFileName};
+pub use stats_index::{IndexEntry, IndexUpdate, StatsIndexFile, StatsIndexManager};
+pub use stats_parser::{
+    decode_stats_bytes, parse_stats_csv, read_run_stats, read_stats_file, read_stats_text,
+    RunStats, StatsRead,
+};
+pub use trend::{
+    analyze_trend, format_trend_report, rolling_averages, Percentiles, ScoreTrend, TrendStatus,
+    DEFAULT_TREND_WINDOW, ROLLING_WINDOW,
+};
+#[cfg(feature = "online")]
+pub use webhook::{
+    beats_previous_best, personal_best_payload, post_webhook, session_payload, PostOutcome,
+    WebhookQueue,
+};
 use std::path::PathBuf;
 
-pub fn get_app_data_dir() -> PathBuf {
+/// The app's own folder, holding `profiles.json` and the default profile's data.
+pub fn get_app_root_dir() -> PathBuf {
     dirs::data_local_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("KovaaksDiscordRPC")
+}
+/// Directory holding the active profile's settings, score store and caches.
+pub fn get_app_data_dir() -> PathBuf {
+    profile_data_dir(&active_profile())
 }
\ No newline at end of file
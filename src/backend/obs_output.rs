@@ -0,0 +1,58 @@
+//! Text and JSON files for OBS "Text (GDI+) from file" sources.
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+const JSON_FILE: &str = "kovaaks.json";
+/// What the overlay files show, empty strings when nothing is being played.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ObsSnapshot {
+    pub status: String,
+    pub scenario: String,
+    pub highscore: String,
+    pub session_best: String,
+    pub playlist: String,
+    pub playlist_progress: String,
+}
+impl ObsSnapshot {
+    fn text_files(&self) -> [(&'static str, &str); 6] {
+        [
+            ("status.txt", &self.status),
+            ("scenario.txt", &self.scenario),
+            ("highscore.txt", &self.highscore),
+            ("session_best.txt", &self.session_best),
+            ("playlist.txt", &self.playlist),
+            ("playlist_progress.txt", &self.playlist_progress),
+        ]
+    }
+}
+/// Writes snapshots into a directory, skipping writes when nothing changed.
+#[derive(Debug, Default)]
+pub struct ObsOutput {
+    last: Option<(PathBuf, ObsSnapshot)>,
+}
+impl ObsOutput {
+    /// Writes the snapshot if it or the directory changed, returns whether files were written.
+    pub fn update(&mut self, directory: &Path, snapshot: &ObsSnapshot) -> Result<bool> {
+        if let Some((last_dir, last_snapshot)) = &self.last {
+            if last_dir == directory && last_snapshot == snapshot {
+                return Ok(false);
+            }
+        }
+        fs::create_dir_all(directory)?;
+        for (file_name, contents) in snapshot.text_files() {
+            write_atomic(&directory.join(file_name), contents)?;
+        }
+        let json = serde_json::to_string_pretty(snapshot)?;
+        write_atomic(&directory.join(JSON_FILE), &json)?;
+        self.last = Some((directory.to_path_buf(), snapshot.clone()));
+        Ok(true)
+    }
+}
+fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    let mut tmp = path.to_path_buf();
+    tmp.set_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
\ No newline at end of file
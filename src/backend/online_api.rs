@@ -1,3 +1,5 @@
+//! Client for the KovaaK's webapp backend.
+use crate::backend::scenario_key::{normalize_scenario_name, scenario_key};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -63,6 +65,7 @@ struct PopularScenarioEntry {
     #[serde(rename = "scenarioName")]
     scenario_name: String,
 }
+/// Fetches a user's online highscores and looks up public scenarios, with an on-disk cache.
 pub struct OnlineScoreAPI {
     base_url: String,
     cache_dir: PathBuf,
@@ -179,6 +182,7 @@ impl OnlineScoreAPI {
         }
         false
     }
+    /// Highscore per scenario for a webapp user, served from cache when fresh.
     pub fn fetch_user_scenario_scores(&self, username: &str) -> Result<HashMap<String, f64>> {
         if username.is_empty() {
             return Ok(HashMap::new());
@@ -233,15 +237,29 @@ impl OnlineScoreAPI {
         if username.is_empty() || scenario_name.is_empty() {
             return None;
         }
+        let key = scenario_key(scenario_name);
         let local_scores = self.load_local_scores();
-        if let Some(score) = local_scores.get(scenario_name) {
-            return Some(*score);
+        if let Some(score) = find_by_key(&local_scores, &key) {
+            return Some(score);
         }
         if let Ok(all_scores) = self.fetch_user_scenario_scores(username) {
-            return all_scores.get(scenario_name).copied();
+            return find_by_key(&all_scores, &key);
         }
         None
     }
+    /// Checks that the webapp backend answers.
+    pub fn ping(&self) -> Result<()> {
+        let url = format!("{}/scenario/popular?page=0&max=1", self.base_url);
+        let response = reqwest::blocking::Client::new()
+            .get(&url)
+            .timeout(std::time::Duration::from_secs(10))
+            .send()?;
+        if !response.status().is_success() {
+            return Err(format!("Webapp answered with status {}", response.status()).into());
+        }
+        Ok(())
+    }
+    /// Whether a scenario exists on the public leaderboards, `Err` when the lookup itself failed.
     pub fn search_scenario_popular(&self, scenario_name: &str) -> Result<bool> {
         if scenario_name.is_empty() {
             return Ok(false);
@@ -256,20 +274,14 @@ impl OnlineScoreAPI {
             .get(&url)
             .timeout(std::time::Duration::from_secs(10))
             .send();
-        let response = match response {
-            Ok(r) => r,
-            Err(_) => return Ok(false),
-        };
+        let response = response?;
         if !response.status().is_success() {
-            return Ok(false);
+            return Err(format!("Scenario search failed with status {}", response.status()).into());
         }
-        let search_response: PopularSearchResponse = match response.json() {
-            Ok(r) => r,
-            Err(_) => return Ok(false),
-        };
-        let scenario_lower = scenario_name.to_lowercase();
+        let search_response: PopularSearchResponse = response.json()?;
+        let key = scenario_key(scenario_name);
         for entry in search_response.data {
-            if entry.scenario_name.to_lowercase() == scenario_lower {
+            if scenario_key(&entry.scenario_name) == key {
                 return Ok(true);
             }
         }
@@ -279,12 +291,13 @@ impl OnlineScoreAPI {
         if username.is_empty() || scenario_name.is_empty() {
             return false;
         }
+        let key = scenario_key(scenario_name);
         let local_scores = self.load_local_scores();
-        if local_scores.contains_key(scenario_name) {
+        if find_by_key(&local_scores, &key).is_some() {
             return true;
         }
         if let Ok(scores) = self.fetch_user_scenario_scores(username) {
-            return scores.contains_key(scenario_name);
+            return find_by_key(&scores, &key).is_some();
         }
         false
     }
@@ -294,7 +307,7 @@ impl OnlineScoreAPI {
         }
         let _ = fs::create_dir_all(&self.raw_scores_dir);
         let client = reqwest::blocking::Client::new();
-        let mut all_scores = HashMap::new();
+        let mut by_key: HashMap<String, (String, f64)> = HashMap::new();
         let mut page = 0;
         let max_per_page = 100;
         let mut _total_entries_fetched = 0;
@@ -334,12 +347,14 @@ impl OnlineScoreAPI {
             }
             let mut _page_scores_added = 0;
             for entry in api_response.data {
-                let scenario = entry.scenario_name.trim().to_string();
+                let scenario = normalize_scenario_name(&entry.scenario_name);
                 let score = entry.score.or_else(|| entry.attributes.as_ref().and_then(|a| a.score));
                 if let Some(score) = score {
                     if !scenario.is_empty() {
-                        let current_max: f64 = all_scores.get(&scenario).copied().unwrap_or(0.0);
-                        all_scores.insert(scenario.clone(), current_max.max(score));
+                        let best = by_key
+                            .entry(scenario_key(&scenario))
+                            .or_insert_with(|| (scenario.clone(), 0.0));
+                        best.1 = best.1.max(score);
                         _page_scores_added += 1;
                     }
                 }
@@ -350,6 +365,14 @@ impl OnlineScoreAPI {
             }
             std::thread::sleep(std::time::Duration::from_millis(100));
         }
-        Ok(all_scores)
+        Ok(by_key.into_values().collect())
     }
 }
+/// Score stored under any name with the same [`scenario_key`].
+fn find_by_key(scores: &HashMap<String, f64>, key: &str) -> Option<f64> {
+    scores
+        .iter()
+        .filter(|(name, _)| scenario_key(name) == key)
+        .map(|(_, score)| *score)
+        .reduce(f64::max)
+}
@@ -0,0 +1,249 @@
+//! Turning the current scenario and scores into Discord presence text.
+use crate::backend::{display_score, format_goal_progress, format_highscore, RunMode, RunStats, Settings};
+const STREAMER_SCENARIO_LABEL: &str = "a scenario";
+const STREAMER_PLAYLIST_LABEL: &str = "a playlist";
+/// Placeholders that show a score or run stat, left empty with hide-scores on.
+const SCORE_PLACEHOLDERS: &[&str] = &[
+    "highscore",
+    "local_highscore",
+    "online_highscore",
+    "session_best",
+    "accuracy",
+    "kills",
+    "avg_ttk",
+    "efficiency",
+    "goal",
+    "goal_progress",
+];
+/// Discord rejects presence text shorter or longer than this.
+const MIN_PRESENCE_TEXT: usize = 2;
+const MAX_PRESENCE_TEXT: usize = 128;
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresenceButton {
+    pub label: String,
+    pub url: String,
+}
+/// Text and button shown on the Discord profile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresenceContent {
+    pub details: String,
+    pub state: Option<String>,
+    pub large_text: String,
+    pub button: Option<PresenceButton>,
+}
+/// Placeholders available in the presence text templates.
+pub const PRESENCE_PLACEHOLDERS: &[&str] = &[
+    "scenario",
+    "mode",
+    "highscore",
+    "local_highscore",
+    "online_highscore",
+    "session_best",
+    "accuracy",
+    "kills",
+    "avg_ttk",
+    "efficiency",
+    "goal",
+    "goal_progress",
+    "streak",
+];
+/// What is being played, before privacy settings are applied.
+#[derive(Debug, Clone, Copy)]
+pub struct PresenceInput<'a> {
+    pub scenario_name: &'a str,
+    /// The scenario's name as the game knows it, for the play button.
+    pub game_name: &'a str,
+    /// Local highscore over the counted modes.
+    pub highscore: f64,
+    pub online_highscore: Option<f64>,
+    pub session_highscore: f64,
+    pub share_code: Option<&'a str>,
+    pub last_run: Option<&'a RunStats>,
+    /// Mode of the latest run, `None` until one was played.
+    pub mode: Option<RunMode>,
+    pub goal: Option<f64>,
+    pub streak: u32,
+}
+fn format_optional(value: Option<f64>, suffix: &str) -> String {
+    match value {
+        Some(v) => format!("{:.1}{}", v, suffix),
+        None => "-".to_string(),
+    }
+}
+fn placeholder_values(input: &PresenceInput, settings: &Settings) -> Vec<(&'static str, String)> {
+    let goal_score = display_score(input.highscore, input.online_highscore, settings.show_online_scores);
+    let scenario = if settings.streamer_mode {
+        if input.share_code.is_some() {
+            STREAMER_PLAYLIST_LABEL.to_string()
+        } else {
+            STREAMER_SCENARIO_LABEL.to_string()
+        }
+    } else {
+        input.scenario_name.to_string()
+    };
+    let run = input.last_run;
+    let mut values = vec![
+        ("scenario", scenario),
+        ("mode", input.mode.map(|mode| mode.label()).unwrap_or("-").to_string()),
+        (
+            "highscore",
+            format_highscore(input.highscore, input.online_highscore, settings.show_online_scores),
+        ),
+        ("local_highscore", format!("{:.1}", input.highscore)),
+        ("online_highscore", format_optional(input.online_highscore, "")),
+        ("session_best", format!("{:.1}", input.session_highscore)),
+        ("accuracy", format_optional(run.and_then(|r| r.accuracy()), "%")),
+        ("kills", run.map(|r| r.kills.to_string()).unwrap_or_else(|| "-".to_string())),
+        (
+            "avg_ttk",
+            match run.and_then(|r| r.avg_ttk) {
+                Some(ttk) => format!("{:.3}s", ttk),
+                None => "-".to_string(),
+            },
+        ),
+        ("efficiency", format_optional(run.and_then(|r| r.damage_efficiency()), "%")),
+        ("goal", input.goal.map(|goal| format!("{:.0}", goal)).unwrap_or_else(|| "-".to_string())),
+        (
+            "goal_progress",
+            input
+                .goal
+                .map(|goal| format_goal_progress(goal_score, goal))
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+        ("streak", input.streak.to_string()),
+    ];
+    if settings.hide_scores {
+        for (name, value) in values.iter_mut() {
+            if SCORE_PLACEHOLDERS.contains(name) {
+                value.clear();
+            }
+        }
+    }
+    values
+}
+/// Trims presence text and cuts it to what Discord accepts, `None` when too short.
+fn fit_presence_text(text: &str) -> Option<String> {
+    let text = text.trim();
+    let length = text.chars().count();
+    if length < MIN_PRESENCE_TEXT {
+        return None;
+    }
+    if length > MAX_PRESENCE_TEXT {
+        let cut: String = text.chars().take(MAX_PRESENCE_TEXT - 1).collect();
+        return Some(format!("{}…", cut.trim_end()));
+    }
+    Some(text.to_string())
+}
+/// Replaces `{name}` placeholders, unknown ones are left as they are.
+pub fn render_template(template: &str, values: &[(&str, String)]) -> String {
+    let mut rendered = template.to_string();
+    for (name, value) in values {
+        rendered = rendered.replace(&format!("{{{}}}", name), value);
+    }
+    rendered
+}
+/// Builds presence content from the text templates, applying the hide-scores and streamer mode settings.
+pub fn build_presence(input: &PresenceInput, settings: &Settings) -> PresenceContent {
+    let values = placeholder_values(input, settings);
+    let scenario = values.iter().find(|(name, _)| *name == "scenario").map(|(_, value)| value.as_str());
+    let details = fit_presence_text(&render_template(&settings.presence_details_template, &values))
+        .or_else(|| fit_presence_text(&format!("Playing: {}", scenario.unwrap_or_default())))
+        .unwrap_or_else(|| "Playing KovaaK's".to_string());
+    let goal_score = display_score(input.highscore, input.online_highscore, settings.show_online_scores);
+    let (state, large_text) = if settings.hide_scores {
+        (None, "KovaaK's".to_string())
+    } else {
+        let state_text = render_template(&settings.presence_state_template, &values);
+        let mut large_text = if input.session_highscore > 0.0 {
+            format!("Session Best: {:.1}", input.session_highscore)
+        } else {
+            "No session plays yet".to_string()
+        };
+        if let Some(mode) = input.mode {
+            large_text = format!("{} · {}", mode.label(), large_text);
+        }
+        if let Some(goal) = input.goal {
+            large_text.push_str(&format!(" · Goal: {}", format_goal_progress(goal_score, goal)));
+        }
+        (fit_presence_text(&state_text), large_text)
+    };
+    let button = if settings.streamer_mode {
+        None
+    } else {
+        Some(play_button(input.game_name, input.share_code))
+    };
+    PresenceContent {
+        details,
+        state,
+        large_text,
+        button,
+    }
+}
+/// Details shown while idle, naming the scenario the idle timeout hit in.
+pub fn idle_details(scenario_name: Option<&str>, settings: &Settings) -> String {
+    match scenario_name {
+        Some(_) if settings.streamer_mode => format!("Idle in {}", STREAMER_SCENARIO_LABEL),
+        Some(name) => format!("Idle in {}", name),
+        None => "Idle in menu".to_string(),
+    }
+}
+fn play_button(scenario_name: &str, share_code: Option<&str>) -> PresenceButton {
+    match share_code {
+        Some(code) => PresenceButton {
+            label: "Play Playlist".to_string(),
+            url: format!("steam://run/824270/?action=jump-to-playlist;sharecode={}", code),
+        },
+        None => {
+            let encoded_scenario = scenario_name.replace(' ', "%20").replace('&', "%26");
+            PresenceButton {
+                label: "Play Scenario".to_string(),
+                url: format!("steam://run/824270/?action=jump-to-scenario;name={}", encoded_scenario),
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn input() -> PresenceInput<'static> {
+        PresenceInput {
+            scenario_name: "Gridshot",
+            game_name: "Gridshot",
+            highscore: 123.4,
+            online_highscore: Some(130.0),
+            session_highscore: 120.0,
+            share_code: None,
+            last_run: None,
+            mode: Some(RunMode::Challenge),
+            goal: Some(150.0),
+            streak: 3,
+        }
+    }
+    #[test]
+    fn hide_scores_blanks_scores_in_both_templates() {
+        let settings = Settings {
+            hide_scores: true,
+            presence_details_template: "{scenario} PB {highscore} / {session_best} goal {goal_progress}".to_string(),
+            ..Settings::default()
+        };
+        let content = build_presence(&input(), &settings);
+        assert_eq!(content.details, "Gridshot PB  /  goal");
+        assert_eq!(content.state, None);
+        assert!(!content.large_text.contains("123.4"));
+    }
+    #[test]
+    fn details_fall_back_when_too_short_and_are_cut_when_too_long() {
+        let short = Settings {
+            presence_details_template: "{kills}".to_string(),
+            ..Settings::default()
+        };
+        assert_eq!(build_presence(&input(), &short).details, "Playing: Gridshot");
+        let long = Settings {
+            presence_details_template: "x".repeat(200),
+            ..Settings::default()
+        };
+        let details = build_presence(&input(), &long).details;
+        assert_eq!(details.chars().count(), MAX_PRESENCE_TEXT);
+        assert!(details.ends_with('…'));
+    }
+}
\ No newline at end of file
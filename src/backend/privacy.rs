@@ -0,0 +1,83 @@
+//! User-managed scenario allow and block lists.
+use crate::backend::Settings;
+/// Case-insensitive glob match supporting `*` and `?`.
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.trim().to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    while p < pattern.len() && pattern[p] == '*' {
+        p += 1;
+    }
+    p == pattern.len()
+}
+fn matches_any(patterns: &[String], scenario_name: &str) -> bool {
+    patterns
+        .iter()
+        .filter(|p| !p.trim().is_empty())
+        .any(|p| glob_matches(p, scenario_name))
+}
+/// Whether the allow and block lists permit showing a scenario.
+pub fn is_scenario_listed_visible(settings: &Settings, scenario_name: &str) -> bool {
+    if matches_any(&settings.scenario_blocklist, scenario_name) {
+        return false;
+    }
+    let has_allowlist = settings.scenario_allowlist.iter().any(|p| !p.trim().is_empty());
+    !has_allowlist || matches_any(&settings.scenario_allowlist, scenario_name)
+}
+/// Splits a one-pattern-per-line text field into patterns.
+pub fn parse_pattern_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn glob_handles_stars_question_marks_and_case() {
+        assert!(glob_matches("gridshot", "Gridshot"));
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("VT *", "VT Pasu Intermediate"));
+        assert!(glob_matches("*Strafe*", "Close Long Strafes"));
+        assert!(glob_matches("1w?ts*", "1w4ts Reload"));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(glob_matches("  tile frenzy  ", "Tile Frenzy"));
+        assert!(!glob_matches("VT *", "Voltaic VT Pasu"));
+        assert!(!glob_matches("gridshot?", "Gridshot"));
+        assert!(!glob_matches("a*b*c", "aXbYbZ"));
+        assert!(!glob_matches("", "Gridshot"));
+    }
+    #[test]
+    fn blocklist_wins_over_allowlist() {
+        let mut settings = Settings {
+            scenario_allowlist: parse_pattern_list("VT *\n\n  *Tracking  \n"),
+            scenario_blocklist: vec!["*Hard*".to_string()],
+            ..Settings::default()
+        };
+        assert_eq!(settings.scenario_allowlist, vec!["VT *", "*Tracking"]);
+        assert!(is_scenario_listed_visible(&settings, "VT Pasu Intermediate"));
+        assert!(is_scenario_listed_visible(&settings, "Smooth Tracking"));
+        assert!(!is_scenario_listed_visible(&settings, "VT Pasu Hard"));
+        assert!(!is_scenario_listed_visible(&settings, "Gridshot"));
+        settings.scenario_allowlist = vec!["  ".to_string()];
+        assert!(is_scenario_listed_visible(&settings, "Gridshot"));
+    }
+}
\ No newline at end of file
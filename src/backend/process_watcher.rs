@@ -0,0 +1,89 @@
+//! Detecting the KovaaK's process without enumerating every process each tick.
+use sysinfo::{Pid, ProcessRefreshKind, System};
+use crate::backend::kovaaks_utils::log_debug;
+const KOVAAKS_PROCESS_NAME: &str = "fpsaimtrainer";
+/// Game start or exit, with the launch time in seconds since the epoch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProcessEvent {
+    Started { pid: u32, launched_at: u64 },
+    Exited { pid: u32 },
+}
+#[derive(Debug, Clone, Copy)]
+struct TrackedProcess {
+    pid: Pid,
+    launched_at: u64,
+}
+/// Keeps one process table around and only checks the cached PID while the game runs.
+pub struct ProcessWatcher {
+    system: System,
+    tracked: Option<TrackedProcess>,
+}
+impl Default for ProcessWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl ProcessWatcher {
+    pub fn new() -> Self {
+        Self {
+            system: System::new(),
+            tracked: None,
+        }
+    }
+    pub fn is_running(&self) -> bool {
+        self.tracked.is_some()
+    }
+    pub fn pid(&self) -> Option<u32> {
+        self.tracked.map(|t| t.pid.as_u32())
+    }
+    pub fn launched_at(&self) -> Option<u64> {
+        self.tracked.map(|t| t.launched_at)
+    }
+    /// Checks the game process, returning an event when it started or exited.
+    pub fn poll(&mut self) -> Option<ProcessEvent> {
+        if let Some(tracked) = self.tracked {
+            if self.is_tracked_alive(tracked) {
+                return None;
+            }
+            self.tracked = None;
+            log_debug(&format!("[DEBUG] Kovaaks process exited: pid {}", tracked.pid));
+            return Some(ProcessEvent::Exited { pid: tracked.pid.as_u32() });
+        }
+        let found = self.full_scan()?;
+        self.tracked = Some(found);
+        log_debug(&format!("[DEBUG] Kovaaks process detected: pid {}", found.pid));
+        Some(ProcessEvent::Started {
+            pid: found.pid.as_u32(),
+            launched_at: found.launched_at,
+        })
+    }
+    /// The PID still belongs to the same game process, a relaunch that got the same PID has another start time.
+    fn is_tracked_alive(&mut self, tracked: TrackedProcess) -> bool {
+        if !self.system.refresh_process_specifics(tracked.pid, ProcessRefreshKind::new()) {
+            return false;
+        }
+
+        self.system
+            .process(tracked.pid)
+            .map(|p| is_kovaaks_process_name(p.name()) && p.start_time() == tracked.launched_at)
+            .unwrap_or(false)
+    }
+    fn full_scan(&mut self) -> Option<TrackedProcess> {
+        self.system.refresh_processes_specifics(ProcessRefreshKind::new());
+        self.system
+            .processes()
+            .iter()
+            .find(|(_, process)| is_kovaaks_process_name(process.name()))
+            .map(|(pid, process)| TrackedProcess {
+                pid: *pid,
+                launched_at: process.start_time(),
+            })
+    }
+}
+fn is_kovaaks_process_name(name: &str) -> bool {
+    let process_name = name.to_lowercase();
+
+    process_name.contains(KOVAAKS_PROCESS_NAME)
+        && !process_name.contains("discord")
+        && !process_name.contains("rpc")
+}
\ No newline at end of file
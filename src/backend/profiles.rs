@@ -0,0 +1,136 @@
+//! Named profiles, each with its own settings, score store and caches in a folder of its own.
+use crate::backend::config::{save_settings_in, Settings};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+/// The profile keeping its data directly in the app folder, where it was before profiles existed.
+pub const DEFAULT_PROFILE: &str = "Default";
+/// Profile [`crate::backend::get_app_data_dir`] points into, empty for the default one.
+static ACTIVE_PROFILE: RwLock<String> = RwLock::new(String::new());
+/// `profiles.json` in the app folder.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfilesFile {
+    #[serde(default)]
+    pub active: String,
+    /// Every profile besides the default one, in the order they were created.
+    #[serde(default)]
+    pub profiles: Vec<String>,
+}
+impl ProfilesFile {
+    /// Every profile name, the default one first.
+    pub fn names(&self) -> Vec<String> {
+        std::iter::once(DEFAULT_PROFILE.to_string())
+            .chain(self.profiles.iter().cloned())
+            .collect()
+    }
+    /// The active profile, the default one when the stored name no longer exists.
+    pub fn active_name(&self) -> &str {
+        if self.profiles.contains(&self.active) {
+            &self.active
+        } else {
+            DEFAULT_PROFILE
+        }
+    }
+}
+/// Makes `name` the profile every manager created from now on reads and writes.
+pub fn set_active_profile(name: &str) {
+    *ACTIVE_PROFILE.write() = if name == DEFAULT_PROFILE {
+        String::new()
+    } else {
+        name.to_string()
+    };
+}
+pub fn active_profile() -> String {
+    let active = ACTIVE_PROFILE.read();
+    if active.is_empty() {
+        DEFAULT_PROFILE.to_string()
+    } else {
+        active.clone()
+    }
+}
+/// Folder holding a profile's settings and data.
+pub fn profile_data_dir(name: &str) -> PathBuf {
+    let root = crate::backend::get_app_root_dir();
+    if name.is_empty() || name == DEFAULT_PROFILE {
+        return root;
+    }
+    root.join("profiles").join(profile_folder_name(name))
+}
+/// Folder name of a profile, characters not allowed in file names replaced by `_`.
+fn profile_folder_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+/// Reads `profiles.json` and activates the stored profile, returning its name.
+pub fn load_active_profile() -> String {
+    let profiles = ProfileManager::new()
+        .and_then(|manager| manager.load())
+        .unwrap_or_default();
+    set_active_profile(profiles.active_name());
+    active_profile()
+}
+pub struct ProfileManager {
+    file_path: PathBuf,
+}
+impl ProfileManager {
+    pub fn new() -> Result<Self> {
+        let app_root_dir = crate::backend::get_app_root_dir();
+        let _ = fs::create_dir_all(&app_root_dir);
+        let file_path = app_root_dir.join("profiles.json");
+        Ok(Self { file_path })
+    }
+    pub fn load(&self) -> Result<ProfilesFile> {
+        if !self.file_path.exists() {
+            return Ok(ProfilesFile::default());
+        }
+        let contents = fs::read_to_string(&self.file_path)?;
+        Ok(serde_json::from_str(&contents).unwrap_or_default())
+    }
+    pub fn save(&self, data: &ProfilesFile) -> Result<()> {
+        let json = serde_json::to_string_pretty(data)?;
+        let mut tmp = self.file_path.clone();
+        tmp.set_extension("tmp");
+        fs::write(&tmp, &json)?;
+        fs::rename(&tmp, &self.file_path)?;
+        Ok(())
+    }
+    /// Creates a profile starting from `settings` without their webapp account, its setup runs on first switch.
+    pub fn create(&self, name: &str, settings: &Settings) -> Result<String> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("Profile name is empty".into());
+        }
+        let mut data = self.load()?;
+        let folder = profile_folder_name(&name).to_lowercase();
+        if data
+            .names()
+            .iter()
+            .any(|existing| profile_folder_name(existing).to_lowercase() == folder)
+        {
+            return Err(format!("A profile named {} already exists", name).into());
+        }
+        let profile_settings = Settings {
+            webapp_username: String::new(),
+            online_scores_synced: false,
+            last_sync_time: 0,
+            setup_complete: false,
+            ..settings.clone()
+        };
+        save_settings_in(&profile_data_dir(&name), &profile_settings)?;
+        data.profiles.push(name.clone());
+        self.save(&data)?;
+        Ok(name)
+    }
+    /// Stores `name` as the profile to load on the next launch.
+    pub fn switch(&self, name: &str) -> Result<()> {
+        let mut data = self.load()?;
+        if name != DEFAULT_PROFILE && !data.profiles.iter().any(|profile| profile == name) {
+            return Err(format!("There is no profile named {}", name).into());
+        }
+        data.active = name.to_string();
+        self.save(&data)
+    }
+}
\ No newline at end of file
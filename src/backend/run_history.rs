@@ -0,0 +1,240 @@
+//! Every run seen while the app was running (`run_history.json`).
+use crate::backend::scenario_key::{normalize_scenario_name, scenario_key};
+use crate::backend::stats_filename::RunMode;
+use crate::backend::stats_parser::RunStats;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+/// One run of a scenario with the settings it was played on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub played_at: u64,
+    pub score: f64,
+    #[serde(default)]
+    pub kills: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_ttk: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sens_scale: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub horizontal_sens: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fov: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cm_per_360: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<RunMode>,
+}
+impl RunRecord {
+    /// Builds a record, cm/360 is worked out with the DPI set at the time.
+    pub fn from_run(run: &RunStats, played_at: Option<SystemTime>, mode: Option<RunMode>, dpi: u32) -> Self {
+        let played_at = played_at
+            .unwrap_or_else(SystemTime::now)
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            played_at,
+            score: run.score,
+            kills: run.kills,
+            accuracy: run.accuracy(),
+            avg_ttk: run.avg_ttk,
+            sens_scale: run.sens_scale.clone(),
+            horizontal_sens: run.horizontal_sens,
+            fov: run.fov,
+            cm_per_360: run.cm_per_360(dpi),
+            mode,
+        }
+    }
+}
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunHistoryFile {
+    pub version: u32,
+    /// Runs keyed by [`scenario_key`].
+    pub runs: HashMap<String, Vec<RunRecord>>,
+    /// Display name of each key.
+    #[serde(default)]
+    pub names: HashMap<String, String>,
+}
+impl Default for RunHistoryFile {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            runs: HashMap::new(),
+            names: HashMap::new(),
+        }
+    }
+}
+/// Scores grouped by the sensitivity they were played on.
+#[derive(Debug, Clone)]
+pub struct SensitivityBucket {
+    pub cm_per_360: f64,
+    pub fov: Option<f64>,
+    pub runs: usize,
+    pub average_score: f64,
+    pub best_score: f64,
+}
+/// Groups runs by cm/360 (to 0.1 cm) and FOV, best average first.
+pub fn sensitivity_breakdown(runs: &[RunRecord]) -> Vec<SensitivityBucket> {
+    let mut groups: HashMap<(i64, Option<i64>), Vec<f64>> = HashMap::new();
+    for run in runs {
+        if let Some(cm) = run.cm_per_360 {
+            let key = ((cm * 10.0).round() as i64, run.fov.map(|fov| (fov * 10.0).round() as i64));
+            groups.entry(key).or_default().push(run.score);
+        }
+    }
+    let mut buckets: Vec<SensitivityBucket> = groups
+        .into_iter()
+        .map(|((cm, fov), scores)| SensitivityBucket {
+            cm_per_360: cm as f64 / 10.0,
+            fov: fov.map(|fov| fov as f64 / 10.0),
+            runs: scores.len(),
+            average_score: scores.iter().sum::<f64>() / scores.len() as f64,
+            best_score: scores.iter().cloned().fold(0.0, f64::max),
+        })
+        .collect();
+    buckets.sort_by(|a, b| b.average_score.total_cmp(&a.average_score));
+    buckets
+}
+/// Reads and writes the run history, every call goes to disk.
+pub struct RunHistoryManager {
+    file_path: PathBuf,
+    /// Held from load to save, so concurrent writers neither lose runs nor share the `.tmp` file.
+    file_lock: Mutex<()>,
+}
+impl RunHistoryManager {
+    pub fn new() -> Result<Self> {
+        let app_data_dir = crate::backend::get_app_data_dir();
+        let _ = fs::create_dir_all(&app_data_dir);
+        let file_path = app_data_dir.join("run_history.json");
+        Ok(Self {
+            file_path,
+            file_lock: Mutex::new(()),
+        })
+    }
+    pub fn load(&self) -> Result<RunHistoryFile> {
+        let _guard = self.file_lock.lock();
+        self.read_file()
+    }
+    fn read_file(&self) -> Result<RunHistoryFile> {
+        if !self.file_path.exists() {
+
+            return Ok(RunHistoryFile::default());
+        }
+        let contents = fs::read_to_string(&self.file_path)?;
+        if contents.trim().is_empty() {
+            return Ok(RunHistoryFile::default());
+        }
+        match serde_json::from_str::<RunHistoryFile>(&contents) {
+            Ok(mut data) => {
+                if rekey_runs(&mut data) {
+                    let _ = self.write_file(&data);
+                }
+                Ok(data)
+            }
+            Err(_) => {
+                let mut backup_path = self.file_path.clone();
+                backup_path.set_extension("bak");
+                let _ = fs::rename(&self.file_path, &backup_path);
+                Ok(RunHistoryFile::default())
+            }
+        }
+    }
+    fn write_file(&self, data: &RunHistoryFile) -> Result<()> {
+        let json = serde_json::to_string_pretty(data)?;
+        let mut tmp_path = self.file_path.clone();
+        tmp_path.set_extension("tmp");
+        fs::write(&tmp_path, &json)?;
+        fs::rename(&tmp_path, &self.file_path)?;
+        Ok(())
+    }
+    /// Appends runs of a scenario, skipping ones already recorded at the same time.
+    pub fn add_runs(&self, scenario_name: &str, records: Vec<RunRecord>) -> Result<usize> {
+        if records.is_empty() {
+            return Ok(0);
+        }
+        let _guard = self.file_lock.lock();
+        let mut data = self.read_file()?;
+        let display_name = normalize_scenario_name(scenario_name);
+        let key = scenario_key(&display_name);
+        data.names.insert(key.clone(), display_name);
+        let runs = data.runs.entry(key).or_default();
+        let mut added = 0;
+        for record in records {
+            let duplicate = runs
+                .iter()
+                .any(|existing| existing.played_at == record.played_at && existing.score == record.score);
+            if !duplicate {
+                runs.push(record);
+                added += 1;
+            }
+        }
+        runs.sort_by_key(|run| run.played_at);
+        if added > 0 {
+            self.write_file(&data)?;
+        }
+        Ok(added)
+    }
+    pub fn get_runs(&self, scenario_name: &str) -> Result<Vec<RunRecord>> {
+        let data = self.load()?;
+        Ok(data
+            .runs
+            .get(&scenario_key(scenario_name))
+            .cloned()
+            .unwrap_or_default())
+    }
+    /// Scenario names with at least one recorded run, sorted case-insensitively.
+    pub fn scenarios(&self) -> Result<Vec<String>> {
+        let data = self.load()?;
+        let mut names: Vec<String> = data
+            .runs
+            .iter()
+            .filter(|(_, runs)| !runs.is_empty())
+            .map(|(key, _)| display_name(&data, key))
+            .collect();
+        names.sort_by_key(|name| name.to_lowercase());
+        Ok(names)
+    }
+}
+/// Name a scenario key is shown under, the key itself for files from before names were kept.
+pub fn display_name(data: &RunHistoryFile, key: &str) -> String {
+    data.names.get(key).cloned().unwrap_or_else(|| key.to_string())
+}
+/// Moves runs stored under older or aliased names to their scenario key, `true` if anything moved.
+fn rekey_runs(data: &mut RunHistoryFile) -> bool {
+    let stale: Vec<String> = data
+        .runs
+        .keys()
+        .filter(|name| scenario_key(name) != **name || !data.names.contains_key(*name))
+        .cloned()
+        .collect();
+    if stale.is_empty() {
+        return false;
+    }
+    for name in stale {
+        let Some(records) = data.runs.remove(&name) else {
+            continue;
+        };
+        let display = normalize_scenario_name(data.names.get(&name).unwrap_or(&name));
+        let key = scenario_key(&display);
+        data.names.remove(&name);
+        data.names.entry(key.clone()).or_insert(display);
+        let runs = data.runs.entry(key).or_default();
+        for record in records {
+            let duplicate = runs
+                .iter()
+                .any(|existing| existing.played_at == record.played_at && existing.score == record.score);
+            if !duplicate {
+                runs.push(record);
+            }
+        }
+        runs.sort_by_key(|run| run.played_at);
+    }
+    true
+}
\ No newline at end of file
@@ -1,13 +1,72 @@
+//! Cache of online scenario lookups used by the "online only" setting.
+use crate::backend::scenario_key::{normalize_scenario_name, scenario_key};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use chrono::{DateTime, Duration, Utc};
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+const POSITIVE_TTL_DAYS: i64 = 30;
+const NEGATIVE_TTL_DAYS: i64 = 3;
+const FAILED_LOOKUP_RETRY_MINUTES: i64 = 5;
+/// A cached lookup result, positive and negative results expire separately.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationEntry {
+    /// Name shown in the settings, entries from before scenario keys have it filled in on load.
+    #[serde(default)]
+    pub scenario_name: String,
+    pub is_valid: bool,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub checked_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manual_override: Option<bool>,
+}
+impl ValidationEntry {
+    pub fn is_expired(&self) -> bool {
+        let ttl = if self.is_valid {
+            Duration::days(POSITIVE_TTL_DAYS)
+        } else {
+            Duration::days(NEGATIVE_TTL_DAYS)
+        };
+        Utc::now().signed_duration_since(self.checked_at) >= ttl
+    }
+    pub fn verdict(&self) -> Option<bool> {
+        if let Some(forced) = self.manual_override {
+            return Some(forced);
+        }
+        if self.is_expired() {
+            return None;
+        }
+        Some(self.is_valid)
+    }
+}
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum StoredEntry {
+    Entry(ValidationEntry),
+    Legacy(bool),
+}
+impl From<StoredEntry> for ValidationEntry {
+    fn from(stored: StoredEntry) -> Self {
+        match stored {
+            StoredEntry::Entry(entry) => entry,
+            StoredEntry::Legacy(is_valid) => ValidationEntry {
+                scenario_name: String::new(),
+                is_valid,
+                checked_at: DateTime::<Utc>::UNIX_EPOCH,
+                manual_override: None,
+            },
+        }
+    }
+}
+/// Persisted lookup results keyed by [`scenario_key`].
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ScenarioValidationCache {
     #[serde(skip)]
     file_path: PathBuf,
-    cache: HashMap<String, bool>,
+    cache: HashMap<String, ValidationEntry>,
+    #[serde(skip)]
+    failed_lookups: HashMap<String, DateTime<Utc>>,
 }
 #[allow(dead_code)]
 impl ScenarioValidationCache {
@@ -18,6 +77,7 @@ impl ScenarioValidationCache {
         let mut cache = Self {
             file_path,
             cache: HashMap::new(),
+            failed_lookups: HashMap::new(),
         };
         let _ = cache.load();
         Ok(cache)
@@ -32,8 +92,33 @@ impl ScenarioValidationCache {
 
             return Ok(());
         }
-        let cache: HashMap<String, bool> = serde_json::from_str(&contents)?;
-        self.cache = cache;
+        let cache: HashMap<String, StoredEntry> = serde_json::from_str(&contents)?;
+        let mut migrated = false;
+        for (stored_key, stored) in cache {
+            let mut entry: ValidationEntry = stored.into();
+            let name = if entry.scenario_name.is_empty() { &stored_key } else { &entry.scenario_name };
+            let display_name = normalize_scenario_name(name);
+            let key = scenario_key(&display_name);
+            if key != stored_key || display_name != entry.scenario_name {
+                migrated = true;
+                entry.scenario_name = display_name;
+            }
+            match self.cache.get_mut(&key) {
+                Some(existing) => {
+                    let manual_override = existing.manual_override.or(entry.manual_override);
+                    if entry.checked_at > existing.checked_at {
+                        *existing = entry;
+                    }
+                    existing.manual_override = manual_override;
+                }
+                None => {
+                    self.cache.insert(key, entry);
+                }
+            }
+        }
+        if migrated {
+            self.save()?;
+        }
         Ok(())
     }
     pub fn save(&self) -> Result<()> {
@@ -44,19 +129,136 @@ impl ScenarioValidationCache {
         fs::rename(&tmp, &self.file_path)?;
         Ok(())
     }
+    /// The manual override, or the cached result if it hasn't expired.
     pub fn is_cached(&self, scenario_name: &str) -> Option<bool> {
-        self.cache.get(scenario_name).copied()
+        self.cache.get(&scenario_key(scenario_name)).and_then(|entry| entry.verdict())
     }
     pub fn insert(&mut self, scenario_name: &str, is_valid: bool) -> Result<()> {
-        self.cache.insert(scenario_name.to_string(), is_valid);
+        let key = scenario_key(scenario_name);
+        self.failed_lookups.remove(&key);
+        let manual_override = self
+            .cache
+            .get(&key)
+            .and_then(|entry| entry.manual_override);
+        self.cache.insert(
+            key,
+            ValidationEntry {
+                scenario_name: normalize_scenario_name(scenario_name),
+                is_valid,
+                checked_at: Utc::now(),
+                manual_override,
+            },
+        );
         self.save()?;
 
         Ok(())
     }
+    pub fn record_failed_lookup(&mut self, scenario_name: &str) {
+        self.failed_lookups.insert(scenario_key(scenario_name), Utc::now());
+    }
+    pub fn recently_failed(&self, scenario_name: &str) -> bool {
+        self.failed_lookups
+            .get(&scenario_key(scenario_name))
+            .map(|failed_at| Utc::now().signed_duration_since(*failed_at) < Duration::minutes(FAILED_LOOKUP_RETRY_MINUTES))
+            .unwrap_or(false)
+    }
+    pub fn set_override(&mut self, scenario_name: &str, manual_override: Option<bool>) -> Result<()> {
+        let entry = self
+            .cache
+            .entry(scenario_key(scenario_name))
+            .or_insert_with(|| ValidationEntry {
+                scenario_name: normalize_scenario_name(scenario_name),
+                is_valid: manual_override.unwrap_or(true),
+                checked_at: DateTime::<Utc>::UNIX_EPOCH,
+                manual_override: None,
+            });
+        entry.manual_override = manual_override;
+        self.save()
+    }
+    pub fn remove(&mut self, scenario_name: &str) -> Result<()> {
+        let key = scenario_key(scenario_name);
+        self.failed_lookups.remove(&key);
+        if self.cache.remove(&key).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+    pub fn purge_expired(&mut self) -> Result<usize> {
+        let before = self.cache.len();
+        self.cache
+            .retain(|_, entry| entry.manual_override.is_some() || !entry.is_expired());
+        let removed = before - self.cache.len();
+        if removed > 0 {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+    pub fn entries(&self) -> Vec<(String, ValidationEntry)> {
+        let mut entries: Vec<(String, ValidationEntry)> = self
+            .cache
+            .values()
+            .map(|entry| (entry.scenario_name.clone(), entry.clone()))
+            .collect();
+        entries.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()));
+        entries
+    }
     pub fn len(&self) -> usize {
         self.cache.len()
     }
     pub fn is_empty(&self) -> bool {
         self.cache.is_empty()
     }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn entry(is_valid: bool, age: Duration) -> ValidationEntry {
+        ValidationEntry {
+            scenario_name: "Gridshot".to_string(),
+            is_valid,
+            checked_at: Utc::now() - age,
+            manual_override: None,
+        }
+    }
+    fn cache_at(name: &str) -> ScenarioValidationCache {
+        let file_path = std::env::temp_dir().join(format!("kovaaks_rpc_{}_{}.json", name, std::process::id()));
+        let _ = fs::remove_file(&file_path);
+        ScenarioValidationCache {
+            file_path,
+            cache: HashMap::new(),
+            failed_lookups: HashMap::new(),
+        }
+    }
+    #[test]
+    fn positive_and_negative_results_expire_separately() {
+        assert_eq!(entry(true, Duration::days(POSITIVE_TTL_DAYS - 1)).verdict(), Some(true));
+        assert_eq!(entry(true, Duration::days(POSITIVE_TTL_DAYS)).verdict(), None);
+        assert_eq!(entry(false, Duration::days(NEGATIVE_TTL_DAYS) - Duration::hours(1)).verdict(), Some(false));
+        assert_eq!(entry(false, Duration::days(NEGATIVE_TTL_DAYS)).verdict(), None);
+    }
+    #[test]
+    fn manual_override_outlives_expiry() {
+        let mut expired = entry(false, Duration::days(365));
+        expired.manual_override = Some(true);
+        assert!(expired.is_expired());
+        assert_eq!(expired.verdict(), Some(true));
+    }
+    #[test]
+    fn expired_entries_are_purged_and_lookups_retried() {
+        let mut cache = cache_at("scenario_cache");
+        cache.insert("Gridshot - Challenge", true).unwrap();
+        assert_eq!(cache.is_cached("gridshot"), Some(true));
+        cache.cache.insert("old negative".to_string(), entry(false, Duration::days(NEGATIVE_TTL_DAYS + 1)));
+        let mut pinned = entry(false, Duration::days(NEGATIVE_TTL_DAYS + 1));
+        pinned.manual_override = Some(false);
+        cache.cache.insert("pinned".to_string(), pinned);
+        assert_eq!(cache.is_cached("Old Negative"), None);
+        assert_eq!(cache.purge_expired().unwrap(), 1);
+        assert_eq!(cache.len(), 2);
+        cache.record_failed_lookup("Tile Frenzy");
+        assert!(cache.recently_failed("tile  frenzy"));
+        cache.failed_lookups.insert(scenario_key("Tile Frenzy"), Utc::now() - Duration::minutes(FAILED_LOOKUP_RETRY_MINUTES));
+        assert!(!cache.recently_failed("Tile Frenzy"));
+        let _ = fs::remove_file(&cache.file_path);
+    }
 }
\ No newline at end of file
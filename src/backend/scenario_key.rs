@@ -0,0 +1,83 @@
+//! Canonical scenario names and keys, and the user's alias table for renamed scenarios.
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use unicode_normalization::UnicodeNormalization;
+const CHALLENGE_SUFFIX: &str = " - Challenge";
+/// Alias keys and the name they map to, installed from the settings.
+static ALIASES: RwLock<Vec<(String, String)>> = RwLock::new(Vec::new());
+/// Installs the alias table, old scenario name to new.
+pub fn set_scenario_aliases(aliases: &BTreeMap<String, String>) {
+    let table = aliases
+        .iter()
+        .map(|(from, to)| (canonical_name(from).to_lowercase(), canonical_name(to)))
+        .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+        .collect();
+    *ALIASES.write() = table;
+}
+/// NFKC-normalized name with collapsed whitespace and without a " - Challenge" suffix.
+fn canonical_name(name: &str) -> String {
+    let normalized: String = name.nfkc().collect();
+    let mut name = normalized.split_whitespace().collect::<Vec<_>>().join(" ");
+    while let Some(start) = name.len().checked_sub(CHALLENGE_SUFFIX.len()) {
+        match name.get(start..) {
+            Some(suffix) if suffix.eq_ignore_ascii_case(CHALLENGE_SUFFIX) => name.truncate(start),
+            _ => break,
+        }
+    }
+    name
+}
+/// The name a scenario is stored and shown under, following the alias table.
+pub fn normalize_scenario_name(name: &str) -> String {
+    let canonical = canonical_name(name);
+    let key = canonical.to_lowercase();
+    ALIASES
+        .read()
+        .iter()
+        .find(|(from, _)| *from == key)
+        .map(|(_, to)| to.clone())
+        .unwrap_or(canonical)
+}
+/// Key every store and lookup uses, so differently cased or spaced names are one scenario.
+pub fn scenario_key(name: &str) -> String {
+    normalize_scenario_name(name).to_lowercase()
+}
+/// Parses one `Old Name = New Name` alias per line, lines without both names are skipped.
+pub fn parse_alias_list(text: &str) -> BTreeMap<String, String> {
+    text.lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(from, to)| (from.trim().to_string(), to.trim().to_string()))
+        .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+        .collect()
+}
+/// The alias table as edited in the settings, one alias per line.
+pub fn format_alias_list(aliases: &BTreeMap<String, String>) -> String {
+    aliases
+        .iter()
+        .map(|(from, to)| format!("{} = {}", from, to))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn keys_ignore_width_whitespace_case_and_challenge_suffix() {
+        assert_eq!(scenario_key("Ｇｒｉｄｓｈｏｔ"), "gridshot");
+        assert_eq!(scenario_key("  Tile   Frenzy\t180 "), "tile frenzy 180");
+        assert_eq!(scenario_key("Close Long Strafes - Challenge"), "close long strafes");
+        assert_eq!(scenario_key("Close Long Strafes - challenge - Challenge"), "close long strafes");
+        assert_eq!(normalize_scenario_name("ﬁnal  Tracking - Challenge"), "final Tracking");
+    }
+    #[test]
+    fn aliases_map_old_names_to_new() {
+        let aliases = parse_alias_list("Old  Smoothbot = Smoothbot Goated\nno separator\n = Empty\n");
+        assert_eq!(aliases.len(), 1);
+        set_scenario_aliases(&aliases);
+        assert_eq!(normalize_scenario_name("old smoothbot - Challenge"), "Smoothbot Goated");
+        assert_eq!(scenario_key("OLD SMOOTHBOT"), scenario_key("Smoothbot Goated"));
+        assert_eq!(normalize_scenario_name("Smoothbot"), "Smoothbot");
+        assert_eq!(format_alias_list(&aliases), "Old  Smoothbot = Smoothbot Goated");
+        set_scenario_aliases(&BTreeMap::new());
+        assert_eq!(normalize_scenario_name("Old Smoothbot"), "Old Smoothbot");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,54 @@
+//! Converting in-game sensitivity to cm/360.
+const CM_PER_INCH: f64 = 2.54;
+/// Degrees turned per mouse count at sensitivity 1 for the sens scales KovaaK's offers.
+/// Only games with a published fixed yaw are listed, scales whose yaw depends on FOV,
+/// game version or extra multipliers (Battlefield, Call of Duty, Fortnite, Rainbow 6 Siege, ...) are left out.
+const YAW_DEGREES: &[(&str, f64)] = &[
+    // Source engine `m_yaw` default (Valve Developer Wiki), also used by the games built on its input code.
+    ("CS:GO", 0.022),
+    ("CS2", 0.022),
+    ("Counter-Strike 2", 0.022),
+    ("Quake/Source", 0.022),
+    ("Source", 0.022),
+    ("Apex Legends", 0.022),
+    ("Apex", 0.022),
+    ("Titanfall 2", 0.022),
+    ("Quake Champions", 0.022),
+    ("Diabotical", 0.022),
+    // Overwatch: 0.0066°/count, Valorant: 0.07°/count, as published by the developers' sensitivity guides.
+    ("Overwatch", 0.0066),
+    ("Valorant", 0.07),
+];
+/// Full turn distance in cm, `None` for sens scales we don't know the yaw of.
+pub fn cm_per_360(sens_scale: &str, sensitivity: f64, dpi: u32) -> Option<f64> {
+    if sensitivity <= 0.0 || dpi == 0 {
+        return None;
+    }
+    let scale = sens_scale.trim();
+    if scale.eq_ignore_ascii_case("cm/360") {
+        return Some(sensitivity);
+    }
+    if scale.eq_ignore_ascii_case("inches/360") || scale.eq_ignore_ascii_case("in/360") {
+        return Some(sensitivity * CM_PER_INCH);
+    }
+    let yaw = YAW_DEGREES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(scale))
+        .map(|(_, yaw)| *yaw)?;
+    let counts = 360.0 / (sensitivity * yaw);
+    Some(counts / dpi as f64 * CM_PER_INCH)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn converts_known_scales_and_skips_unknown_ones() {
+        let cs2 = cm_per_360("CS2", 1.0, 800).unwrap();
+        assert!((cs2 - 51.95).abs() < 0.01);
+        let valorant = cm_per_360("valorant", 0.5, 800).unwrap();
+        assert!((valorant - 32.66).abs() < 0.01);
+        assert_eq!(cm_per_360("cm/360", 34.0, 800), Some(34.0));
+        assert_eq!(cm_per_360("Battlefield", 1.0, 800), None);
+        assert_eq!(cm_per_360("CS2", 1.0, 0), None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,238 @@
+//! Summaries of play sessions, written to `sessions.json` when the game closes.
+use crate::backend::config::CountedModes;
+use crate::backend::run_history::{display_name, RunHistoryFile, RunRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+/// Runs before the session that the session average is compared against.
+const PREVIOUS_RUNS_COMPARED: usize = 20;
+/// How one scenario went during a session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioSummary {
+    pub scenario: String,
+    pub runs: usize,
+    pub best_score: f64,
+    pub average_score: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_best: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_average: Option<f64>,
+    pub is_pb: bool,
+}
+impl ScenarioSummary {
+    /// Session average minus the average of the runs before it, in percent.
+    pub fn improvement(&self) -> Option<f64> {
+        let previous = self.previous_average?;
+        if previous <= 0.0 {
+            return None;
+        }
+        Some((self.average_score - previous) / previous * 100.0)
+    }
+}
+/// Everything played between the game starting and closing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub active_secs: u64,
+    pub scenarios: Vec<ScenarioSummary>,
+}
+impl SessionSummary {
+    /// Builds a summary of the runs in the `counted` modes, `None` when nothing was played.
+    /// `start_highscores` is keyed by scenario key like the history and holds highscores over the same modes.
+    pub fn build(
+        history: &RunHistoryFile,
+        started_at: u64,
+        ended_at: u64,
+        active_secs: u64,
+        start_highscores: &HashMap<String, f64>,
+        counted: CountedModes,
+    ) -> Option<Self> {
+        let mut scenarios = Vec::new();
+        for (key, runs) in &history.runs {
+            let (previous, played): (Vec<&RunRecord>, Vec<&RunRecord>) = runs
+                .iter()
+                .filter(|run| run.played_at <= ended_at && counted.counts_run(run.mode))
+                .partition(|run| run.played_at < started_at);
+            if played.is_empty() {
+                continue;
+            }
+            let best_score = played.iter().map(|run| run.score).fold(0.0, f64::max);
+            let average_score = played.iter().map(|run| run.score).sum::<f64>() / played.len() as f64;
+            let history_best = previous.iter().map(|run| run.score).reduce(f64::max);
+            let previous_best = match (start_highscores.get(key).copied(), history_best) {
+                (Some(stored), Some(history)) => Some(stored.max(history)),
+                (stored, history) => stored.or(history).filter(|best| *best > 0.0),
+            };
+            let compared: Vec<f64> = previous
+                .iter()
+                .rev()
+                .take(PREVIOUS_RUNS_COMPARED)
+                .map(|run| run.score)
+                .collect();
+            let previous_average = if compared.is_empty() {
+                None
+            } else {
+                Some(compared.iter().sum::<f64>() / compared.len() as f64)
+            };
+            scenarios.push(ScenarioSummary {
+                scenario: display_name(history, key),
+                runs: played.len(),
+                best_score,
+                average_score,
+                previous_best,
+                previous_average,
+                is_pb: previous_best.is_some_and(|previous| best_score > previous),
+            });
+        }
+        if scenarios.is_empty() {
+            return None;
+        }
+        scenarios.sort_by(|a, b| b.runs.cmp(&a.runs).then_with(|| a.scenario.cmp(&b.scenario)));
+        Some(Self {
+            started_at,
+            ended_at,
+            active_secs,
+            scenarios,
+        })
+    }
+    pub fn total_runs(&self) -> usize {
+        self.scenarios.iter().map(|scenario| scenario.runs).sum()
+    }
+    pub fn personal_bests(&self) -> impl Iterator<Item = &ScenarioSummary> {
+        self.scenarios.iter().filter(|scenario| scenario.is_pb)
+    }
+    /// Scenarios that beat their previous average, biggest gain first.
+    pub fn best_improvements(&self) -> Vec<(&ScenarioSummary, f64)> {
+        let mut improved: Vec<(&ScenarioSummary, f64)> = self
+            .scenarios
+            .iter()
+            .filter_map(|scenario| scenario.improvement().map(|gain| (scenario, gain)))
+            .filter(|(_, gain)| *gain > 0.0)
+            .collect();
+        improved.sort_by(|a, b| b.1.total_cmp(&a.1));
+        improved
+    }
+    /// One line for toasts and logs.
+    pub fn headline(&self) -> String {
+        let pbs = self.personal_bests().count();
+        format!(
+            "Session over: {} runs of {} scenarios in {}, {} new PB{}",
+            self.total_runs(),
+            self.scenarios.len(),
+            format_duration(self.active_secs),
+            pbs,
+            if pbs == 1 { "" } else { "s" }
+        )
+    }
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# KovaaK's session {}\n\n", format_timestamp(self.started_at));
+        out.push_str(&format!("- Played: {}\n", format_duration(self.active_secs)));
+        out.push_str(&format!("- Scenarios: {}\n", self.scenarios.len()));
+        out.push_str(&format!("- Runs: {}\n", self.total_runs()));
+        out.push_str(&format!("- New PBs: {}\n\n", self.personal_bests().count()));
+        out.push_str("| Scenario | Runs | Best | Average | Previous Avg | Change |\n");
+        out.push_str("|---|---|---|---|---|---|\n");
+        for scenario in &self.scenarios {
+            out.push_str(&format!(
+                "| {}{} | {} | {:.1} | {:.1} | {} | {} |\n",
+                scenario.scenario,
+                if scenario.is_pb { " (PB)" } else { "" },
+                scenario.runs,
+                scenario.best_score,
+                scenario.average_score,
+                scenario
+                    .previous_average
+                    .map(|average| format!("{:.1}", average))
+                    .unwrap_or_else(|| "-".to_string()),
+                scenario
+                    .improvement()
+                    .map(|gain| format!("{:+.1}%", gain))
+                    .unwrap_or_else(|| "-".to_string()),
+            ));
+        }
+        out
+    }
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+pub fn format_duration(secs: u64) -> String {
+    let hours = secs / 3600;
+    let minutes = (secs % 3600) / 60;
+    if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+pub fn format_timestamp(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionLogFile {
+    pub version: u32,
+    pub sessions: Vec<SessionSummary>,
+}
+impl Default for SessionLogFile {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            sessions: Vec::new(),
+        }
+    }
+}
+/// Reads and writes the sessions log, every call goes to disk.
+pub struct SessionLogManager {
+    file_path: PathBuf,
+}
+impl SessionLogManager {
+    pub fn new() -> Result<Self> {
+        let app_data_dir = crate::backend::get_app_data_dir();
+        let _ = fs::create_dir_all(&app_data_dir);
+        let file_path = app_data_dir.join("sessions.json");
+        Ok(Self { file_path })
+    }
+    pub fn load(&self) -> Result<SessionLogFile> {
+        if !self.file_path.exists() {
+
+            return Ok(SessionLogFile::default());
+        }
+        let contents = fs::read_to_string(&self.file_path)?;
+        if contents.trim().is_empty() {
+            return Ok(SessionLogFile::default());
+        }
+        match serde_json::from_str::<SessionLogFile>(&contents) {
+            Ok(data) => Ok(data),
+            Err(_) => {
+                let mut backup_path = self.file_path.clone();
+                backup_path.set_extension("bak");
+                let _ = fs::rename(&self.file_path, &backup_path);
+                Ok(SessionLogFile::default())
+            }
+        }
+    }
+    pub fn save(&self, data: &SessionLogFile) -> Result<()> {
+        let json = serde_json::to_string_pretty(data)?;
+        let mut tmp_path = self.file_path.clone();
+        tmp_path.set_extension("tmp");
+        fs::write(&tmp_path, &json)?;
+        fs::rename(&tmp_path, &self.file_path)?;
+        Ok(())
+    }
+    pub fn append(&self, summary: SessionSummary) -> Result<()> {
+        let mut data = self.load()?;
+        data.sessions.push(summary);
+        self.save(&data)
+    }
+    /// Logged sessions, most recent first.
+    pub fn sessions(&self) -> Result<Vec<SessionSummary>> {
+        let mut sessions = self.load()?.sessions;
+        sessions.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        Ok(sessions)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,129 @@
+//! Parsing KovaaK's stats file names: `{scenario} - [Challenge - ]YYYY.MM.DD-HH.MM.SS Stats.csv`.
+use crate::backend::scenario_key::scenario_key;
+use chrono::{Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+const STATS_SUFFIX: &str = " Stats.csv";
+const CHALLENGE_MARKER: &str = " - Challenge";
+const TIMESTAMP_FORMAT: &str = "%Y.%m.%d-%H.%M.%S";
+/// Whether a run was a scored challenge or freeplay practice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RunMode {
+    Challenge,
+    Freeplay,
+}
+impl RunMode {
+    pub const ALL: [RunMode; 2] = [RunMode::Challenge, RunMode::Freeplay];
+    pub fn label(&self) -> &'static str {
+        match self {
+            RunMode::Challenge => "Challenge",
+            RunMode::Freeplay => "Freeplay",
+        }
+    }
+}
+/// The parts of a stats file name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsFileName {
+    pub scenario: String,
+    pub mode: RunMode,
+    /// Local time the run finished, as written by the game.
+    pub played_at: NaiveDateTime,
+}
+impl StatsFileName {
+    /// Parses a file name, `None` for anything that isn't a stats file.
+    pub fn parse(file_name: &str) -> Option<Self> {
+        let stem = file_name.strip_suffix(STATS_SUFFIX)?;
+        let (rest, timestamp) = stem.rsplit_once(" - ")?;
+        let played_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+        let (scenario, mode) = match rest.strip_suffix(CHALLENGE_MARKER) {
+            Some(scenario) => (scenario, RunMode::Challenge),
+            None => (rest, RunMode::Freeplay),
+        };
+        if scenario.trim().is_empty() {
+            return None;
+        }
+        Some(Self {
+            scenario: scenario.to_string(),
+            mode,
+            played_at,
+        })
+    }
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::parse(path.file_name()?.to_str()?)
+    }
+    /// Whether this run is of `scenario_name`, compared by [`scenario_key`] so aliases and spelling variants match.
+    pub fn is_scenario(&self, scenario_name: &str) -> bool {
+        scenario_key(&self.scenario) == scenario_key(scenario_name)
+    }
+    /// When the run was played, `None` for a local time that doesn't exist (DST gap).
+    pub fn played_at_system_time(&self) -> Option<SystemTime> {
+        Local
+            .from_local_datetime(&self.played_at)
+            .earliest()
+            .map(SystemTime::from)
+    }
+    pub fn played_at_unix(&self) -> Option<u64> {
+        self.played_at_system_time()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs())
+    }
+}
+/// Every stats file in the folder with its parsed name, other files are skipped.
+pub fn list_stats_files(stats_dir: &Path) -> Vec<(PathBuf, StatsFileName)> {
+    let Ok(entries) = fs::read_dir(stats_dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = StatsFileName::from_path(&path)?;
+            Some((path, name))
+        })
+        .collect()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, mo, d).unwrap().and_hms_opt(h, mi, s).unwrap()
+    }
+    #[test]
+    fn parses_freeplay_and_challenge_runs() {
+        let freeplay = StatsFileName::parse("Gridshot - 2024.03.05-18.22.41 Stats.csv").unwrap();
+        assert_eq!(freeplay.scenario, "Gridshot");
+        assert_eq!(freeplay.mode, RunMode::Freeplay);
+        assert_eq!(freeplay.played_at, at(2024, 3, 5, 18, 22, 41));
+        let challenge = StatsFileName::parse("Gridshot - Challenge - 2024.03.05-18.22.41 Stats.csv").unwrap();
+        assert_eq!(challenge.scenario, "Gridshot");
+        assert_eq!(challenge.mode, RunMode::Challenge);
+        assert_eq!(challenge.played_at, at(2024, 3, 5, 18, 22, 41));
+    }
+    #[test]
+    fn keeps_separators_inside_the_scenario_name() {
+        let freeplay = StatsFileName::parse("1w4ts - Reload - Small - 2023.12.31-23.59.59 Stats.csv").unwrap();
+        assert_eq!(freeplay.scenario, "1w4ts - Reload - Small");
+        assert_eq!(freeplay.mode, RunMode::Freeplay);
+        let challenge = StatsFileName::parse("1w4ts - Reload - Challenge - 2023.12.31-23.59.59 Stats.csv").unwrap();
+        assert_eq!(challenge.scenario, "1w4ts - Reload");
+        assert_eq!(challenge.mode, RunMode::Challenge);
+    }
+    #[test]
+    fn rejects_other_files() {
+        assert_eq!(StatsFileName::parse("Gridshot - 2024.03.05-18.22.41 Stats.txt"), None);
+        assert_eq!(StatsFileName::parse("Gridshot - 2024.13.05-18.22.41 Stats.csv"), None);
+        assert_eq!(StatsFileName::parse("Gridshot 2024.03.05-18.22.41 Stats.csv"), None);
+        assert_eq!(StatsFileName::parse(" - Challenge - 2024.03.05-18.22.41 Stats.csv"), None);
+        assert_eq!(StatsFileName::parse("settings.json"), None);
+    }
+    #[test]
+    fn matches_scenarios_by_key() {
+        let parsed = StatsFileName::parse("Tile  Frenzy - Challenge - 2024.03.05-18.22.41 Stats.csv").unwrap();
+        assert!(parsed.is_scenario("tile frenzy"));
+        assert!(!parsed.is_scenario("Tile Frenzy 180"));
+    }
+}
\ No newline at end of file
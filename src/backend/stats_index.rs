@@ -0,0 +1,193 @@
+//! Persisted index of the stats folder (`stats_index.json`), so a rescan only parses new or changed files.
+use crate::backend::stats_filename::{RunMode, StatsFileName};
+use crate::backend::stats_parser::{read_stats_file, StatsRead};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::SystemTime;
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+/// Files parsed between two saves of the index, so a cancelled scan keeps its progress.
+const SAVE_EVERY: usize = 2000;
+/// Index format, entries of older indexes lack the fight time and are parsed again.
+const INDEX_VERSION: u32 = 2;
+/// A parsed stats file, valid while its size and modification time stay the same.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub size: u64,
+    pub modified: u64,
+    /// `None` when the file couldn't be read.
+    pub score: Option<f64>,
+    #[serde(default)]
+    pub fight_time: Option<f64>,
+}
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatsIndexFile {
+    pub version: u32,
+    /// Entries keyed by file name.
+    pub entries: HashMap<String, IndexEntry>,
+}
+impl Default for StatsIndexFile {
+    fn default() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            entries: HashMap::new(),
+        }
+    }
+}
+impl StatsIndexFile {
+    /// Best score and last played time of every scenario and mode, like [`crate::backend::scan_all_stats_folder`].
+    pub fn best_scores(&self) -> HashMap<(String, RunMode), (f64, Option<SystemTime>)> {
+        let mut best: HashMap<(String, RunMode), (f64, Option<SystemTime>)> = HashMap::new();
+        for (file_name, entry) in &self.entries {
+            let (Some(score), Some(parsed)) = (entry.score, StatsFileName::parse(file_name)) else {
+                continue;
+            };
+            let played_at = parsed.played_at_system_time();
+            best.entry((parsed.scenario, parsed.mode))
+                .and_modify(|(best_score, best_time)| {
+                    if score > *best_score {
+                        *best_score = score;
+                        *best_time = played_at;
+                    }
+                })
+                .or_insert((score, played_at));
+        }
+        best
+    }
+}
+/// Result of bringing the index up to date.
+#[derive(Debug)]
+pub struct IndexUpdate {
+    pub index: StatsIndexFile,
+    pub parsed: usize,
+    pub cancelled: bool,
+}
+/// Reads and writes the stats index, every call goes to disk.
+pub struct StatsIndexManager {
+    file_path: PathBuf,
+}
+impl StatsIndexManager {
+    pub fn new() -> Result<Self> {
+        let app_data_dir = crate::backend::get_app_data_dir();
+        let _ = fs::create_dir_all(&app_data_dir);
+        let file_path = app_data_dir.join("stats_index.json");
+        Ok(Self { file_path })
+    }
+    pub fn load(&self) -> Result<StatsIndexFile> {
+        if !self.file_path.exists() {
+            return Ok(StatsIndexFile::default());
+        }
+        let contents = fs::read_to_string(&self.file_path)?;
+        let data: StatsIndexFile = serde_json::from_str(&contents).unwrap_or_default();
+        if data.version < INDEX_VERSION {
+            return Ok(StatsIndexFile::default());
+        }
+        Ok(data)
+    }
+    pub fn save(&self, data: &StatsIndexFile) -> Result<()> {
+        let json = serde_json::to_string(data)?;
+        let mut tmp_path = self.file_path.clone();
+        tmp_path.set_extension("tmp");
+        fs::write(&tmp_path, &json)?;
+        fs::rename(&tmp_path, &self.file_path)?;
+        Ok(())
+    }
+    /// Parses new and changed stats files on all cores and drops deleted ones.
+    /// `progress` gets (done, total) of the files to parse, `cancel` stops early and keeps what was parsed.
+    pub fn update(
+        &self,
+        stats_dir: &Path,
+        cancel: &AtomicBool,
+        progress: &(dyn Fn(usize, usize) + Sync),
+    ) -> Result<IndexUpdate> {
+        let mut index = self.load()?;
+        let mut present: HashMap<String, (PathBuf, u64, u64)> = HashMap::new();
+        for entry in fs::read_dir(stats_dir)?.flatten() {
+            let path = entry.path();
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()).map(str::to_string) else {
+                continue;
+            };
+            if StatsFileName::parse(&file_name).is_none() {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            present.insert(file_name, (path, metadata.len(), modified));
+        }
+        index.entries.retain(|file_name, _| present.contains_key(file_name));
+        let pending: Vec<(String, PathBuf, u64, u64)> = present
+            .into_iter()
+            .filter(|(file_name, (_, size, modified))| {
+                !index
+                    .entries
+                    .get(file_name)
+                    .is_some_and(|entry| entry.size == *size && entry.modified == *modified)
+            })
+            .map(|(file_name, (path, size, modified))| (file_name, path, size, modified))
+            .collect();
+        let total = pending.len();
+        let next = AtomicUsize::new(0);
+        let done = AtomicUsize::new(0);
+        let parsed = Mutex::new(Vec::with_capacity(total));
+        let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        for batch in pending.chunks(SAVE_EVERY) {
+            std::thread::scope(|scope| {
+                for _ in 0..workers.min(batch.len()) {
+                    scope.spawn(|| loop {
+                        if cancel.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let Some((file_name, path, size, modified)) = batch.get(next.fetch_add(1, Ordering::Relaxed))
+                        else {
+                            break;
+                        };
+                        let run = match read_stats_file(path) {
+                            StatsRead::Run(run) => Some(run),
+                            StatsRead::Unreadable => None,
+                            // Left out of the index so the next scan reads it again.
+                            StatsRead::Incomplete => {
+                                progress(done.fetch_add(1, Ordering::Relaxed) + 1, total);
+                                continue;
+                            }
+                        };
+                        let entry = IndexEntry {
+                            size: *size,
+                            modified: *modified,
+                            score: run.as_ref().map(|run| run.score),
+                            fight_time: run.as_ref().map(|run| run.fight_time),
+                        };
+                        parsed.lock().push((file_name.clone(), entry));
+                        progress(done.fetch_add(1, Ordering::Relaxed) + 1, total);
+                    });
+                }
+            });
+            next.store(0, Ordering::Relaxed);
+            index.entries.extend(parsed.lock().drain(..));
+            self.save(&index)?;
+            if cancel.load(Ordering::Relaxed) {
+                break;
+            }
+        }
+        if total == 0 {
+            self.save(&index)?;
+        }
+        Ok(IndexUpdate {
+            index,
+            parsed: done.load(Ordering::Relaxed),
+            cancelled: cancel.load(Ordering::Relaxed),
+        })
+    }
+    pub fn get_path(&self) -> &PathBuf {
+        &self.file_path
+    }
+}
\ No newline at end of file
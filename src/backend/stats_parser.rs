@@ -0,0 +1,210 @@
+//! Parsing the per-run stats CSVs KovaaK's writes into the stats folder.
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+const WEAPON_HEADER: &str = "Weapon,Shots,Hits,Damage Done,Damage Possible";
+/// Share of zero bytes on one side above which a file without BOM is taken for UTF-16.
+const UTF16_ZERO_SHARE: f64 = 0.3;
+/// A file without a score changed more recently than this is taken to be still being written.
+const STILL_WRITING: Duration = Duration::from_secs(10);
+/// Figures from one run's stats file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunStats {
+    pub score: f64,
+    pub kills: u32,
+    pub fight_time: f64,
+    pub avg_ttk: Option<f64>,
+    pub shots: u32,
+    pub hits: u32,
+    pub damage_done: f64,
+    pub damage_possible: f64,
+    pub sens_scale: Option<String>,
+    pub horizontal_sens: Option<f64>,
+    pub fov: Option<f64>,
+}
+impl RunStats {
+    /// Hits per shot as a percentage, `None` for runs without shots.
+    pub fn accuracy(&self) -> Option<f64> {
+        if self.shots == 0 {
+            return None;
+        }
+        Some(self.hits as f64 / self.shots as f64 * 100.0)
+    }
+    /// Damage done per damage possible as a percentage.
+    pub fn damage_efficiency(&self) -> Option<f64> {
+        if self.damage_possible <= 0.0 {
+            return None;
+        }
+        Some(self.damage_done / self.damage_possible * 100.0)
+    }
+    /// Turn distance of the run's sensitivity at the given mouse DPI.
+    pub fn cm_per_360(&self, dpi: u32) -> Option<f64> {
+        crate::backend::sensitivity::cm_per_360(self.sens_scale.as_deref()?, self.horizontal_sens?, dpi)
+    }
+}
+fn parse_number<T: std::str::FromStr>(value: &str) -> Option<T> {
+    value.trim().trim_end_matches('s').parse::<T>().ok()
+}
+/// Parses the contents of a stats CSV.
+pub fn parse_stats_csv(content: &str) -> RunStats {
+    let mut stats = RunStats::default();
+    let mut in_weapon_section = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with(WEAPON_HEADER) {
+            in_weapon_section = true;
+            continue;
+        }
+        if in_weapon_section {
+            if line.is_empty() {
+                in_weapon_section = false;
+                continue;
+            }
+            let columns: Vec<&str> = line.split(',').collect();
+            if columns.len() >= 5 {
+                stats.shots += parse_number::<u32>(columns[1]).unwrap_or(0);
+                stats.hits += parse_number::<u32>(columns[2]).unwrap_or(0);
+                stats.damage_done += parse_number::<f64>(columns[3]).unwrap_or(0.0);
+                stats.damage_possible += parse_number::<f64>(columns[4]).unwrap_or(0.0);
+            }
+            continue;
+        }
+        if let Some((key, rest)) = line.split_once(":,") {
+            let value = rest.split(',').next().unwrap_or("");
+            match key {
+                "Score" => stats.score = parse_number(value).unwrap_or(0.0),
+                "Kills" => stats.kills = parse_number(value).unwrap_or(0),
+                "Fight Time" => stats.fight_time = parse_number(value).unwrap_or(0.0),
+                "Avg TTK" => stats.avg_ttk = parse_number(value),
+                "Sens Scale" => stats.sens_scale = Some(value.trim().to_string()).filter(|s| !s.is_empty()),
+                "Horiz Sens" => stats.horizontal_sens = parse_number(value),
+                "FOV" => stats.fov = parse_number(value),
+                _ => {}
+            }
+        }
+    }
+    stats
+}
+/// Outcome of reading one stats file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatsRead {
+    Run(RunStats),
+    /// No score yet in a recently changed file, KovaaK's is probably still writing it.
+    Incomplete,
+    /// The file can't be read, or still has no score once it stopped changing.
+    Unreadable,
+}
+/// Reads and parses a stats CSV, telling files that are still being written from broken ones.
+pub fn read_stats_file(path: &Path) -> StatsRead {
+    let Ok(content) = read_stats_text(path) else {
+        return StatsRead::Unreadable;
+    };
+    if content.contains("Score:,") {
+        return StatsRead::Run(parse_stats_csv(&content));
+    }
+    let still_writing = fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_none_or(|age| age < STILL_WRITING);
+    if still_writing {
+        StatsRead::Incomplete
+    } else {
+        StatsRead::Unreadable
+    }
+}
+/// Reads and parses a stats CSV, `None` when the file can't be read or has no score.
+pub fn read_run_stats(path: &Path) -> Option<RunStats> {
+    match read_stats_file(path) {
+        StatsRead::Run(run) => Some(run),
+        StatsRead::Incomplete | StatsRead::Unreadable => None,
+    }
+}
+/// Reads a stats file as text whatever its encoding, see [`decode_stats_bytes`].
+pub fn read_stats_text(path: &Path) -> io::Result<String> {
+    Ok(decode_stats_bytes(&fs::read(path)?))
+}
+/// Decodes UTF-8 or UTF-16 with or without a BOM, invalid bytes become U+FFFD instead of failing.
+pub fn decode_stats_bytes(bytes: &[u8]) -> String {
+    let encoding = Encoding::for_bom(bytes)
+        .map(|(encoding, _)| encoding)
+        .or_else(|| guess_utf16(bytes))
+        .unwrap_or(UTF_8);
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+/// UTF-16 without a BOM shows up as mostly-ASCII text with every other byte zero.
+fn guess_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let pairs = bytes.len() / 2;
+    if pairs == 0 {
+        return None;
+    }
+    let zeros_at = |offset: usize| bytes.iter().skip(offset).step_by(2).filter(|byte| **byte == 0).count();
+    let share = |count: usize| count as f64 / pairs as f64;
+    if share(zeros_at(1)) > UTF16_ZERO_SHARE {
+        Some(UTF_16LE)
+    } else if share(zeros_at(0)) > UTF16_ZERO_SHARE {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const CSV: &str = "Kill #,Timestamp\nScore:,812.5\nFight Time:,60.0\n";
+    fn utf16(text: &str, big_endian: bool, bom: bool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if bom {
+            bytes.extend(if big_endian { [0xFE, 0xFF] } else { [0xFF, 0xFE] });
+        }
+        for unit in text.encode_utf16() {
+            bytes.extend(if big_endian { unit.to_be_bytes() } else { unit.to_le_bytes() });
+        }
+        bytes
+    }
+    #[test]
+    fn decodes_utf8_with_and_without_bom() {
+        assert_eq!(decode_stats_bytes(CSV.as_bytes()), CSV);
+        let mut with_bom = vec![0xEF, 0xBB, 0xBF];
+        with_bom.extend_from_slice(CSV.as_bytes());
+        assert_eq!(decode_stats_bytes(&with_bom), CSV);
+    }
+    #[test]
+    fn decodes_utf16_with_and_without_bom() {
+        for big_endian in [false, true] {
+            for bom in [false, true] {
+                assert_eq!(decode_stats_bytes(&utf16(CSV, big_endian, bom)), CSV, "big endian {} bom {}", big_endian, bom);
+            }
+        }
+    }
+    #[test]
+    fn invalid_bytes_are_replaced() {
+        let text = decode_stats_bytes(b"Score:,1\xFF00\n");
+        assert_eq!(text, "Score:,1\u{FFFD}00\n");
+        assert_eq!(decode_stats_bytes(&[]), "");
+    }
+    #[test]
+    fn parses_decoded_utf16_files() {
+        let stats = parse_stats_csv(&decode_stats_bytes(&utf16(CSV, false, true)));
+        assert_eq!(stats.score, 812.5);
+        assert_eq!(stats.fight_time, 60.0);
+    }
+    #[test]
+    fn files_without_a_score_are_incomplete_until_they_settle() {
+        let path = std::env::temp_dir().join(format!("kovaaks_rpc_stats_{}.csv", std::process::id()));
+        fs::write(&path, "Kill #,Timestamp\n1,12:00:00\n").unwrap();
+        assert_eq!(read_stats_file(&path), StatsRead::Incomplete);
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - STILL_WRITING * 2).unwrap();
+        drop(file);
+        assert_eq!(read_stats_file(&path), StatsRead::Unreadable);
+        fs::write(&path, CSV).unwrap();
+        assert!(matches!(read_stats_file(&path), StatsRead::Run(run) if run.score == 812.5));
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read_stats_file(&path), StatsRead::Unreadable);
+    }
+}
\ No newline at end of file
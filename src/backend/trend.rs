@@ -0,0 +1,262 @@
+//! Rolling averages, percentiles, linear trend and plateau detection over a scenario's runs.
+use crate::backend::config::CountedModes;
+use crate::backend::run_history::RunRecord;
+/// Runs looked at when no window is given.
+pub const DEFAULT_TREND_WINDOW: usize = 20;
+/// Runs averaged for the rolling average.
+pub const ROLLING_WINDOW: usize = 5;
+const MIN_TREND_RUNS: usize = 3;
+const MIN_PLATEAU_RUNS: usize = 10;
+/// Change over the window, relative to its average, below which progress counts as flat.
+const FLAT_THRESHOLD_PERCENT: f64 = 2.0;
+/// Where a scenario is heading over the trend window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrendStatus {
+    Improving,
+    Declining,
+    Steady,
+    Plateau,
+}
+impl TrendStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrendStatus::Improving => "Improving",
+            TrendStatus::Declining => "Declining",
+            TrendStatus::Steady => "Steady",
+            TrendStatus::Plateau => "Plateau",
+        }
+    }
+}
+/// Score spread over the trend window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Percentiles {
+    pub p10: f64,
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p90: f64,
+}
+/// Trend analysis of the last `window` runs of a scenario.
+#[derive(Debug, Clone)]
+pub struct ScoreTrend {
+    pub total_runs: usize,
+    pub window: usize,
+    pub best_score: f64,
+    pub runs_since_best: usize,
+    pub rolling_average: f64,
+    pub previous_rolling_average: Option<f64>,
+    pub window_average: f64,
+    pub percentiles: Percentiles,
+    /// Least-squares slope in points per run.
+    pub slope_per_run: f64,
+    pub status: TrendStatus,
+}
+impl ScoreTrend {
+    /// Change predicted by the trend line over the whole window, in % of the window average.
+    pub fn change_percent(&self) -> f64 {
+        if self.window_average <= 0.0 {
+            return 0.0;
+        }
+        self.slope_per_run * self.window.saturating_sub(1) as f64 / self.window_average * 100.0
+    }
+}
+/// Analyzes the last `window` of `runs` (oldest first) in the `counted` modes, `None` with fewer than three such runs.
+pub fn analyze_trend(runs: &[RunRecord], window: usize, counted: CountedModes) -> Option<ScoreTrend> {
+    let scores: Vec<f64> = runs
+        .iter()
+        .filter(|run| counted.counts_run(run.mode))
+        .map(|run| run.score)
+        .collect();
+    if scores.len() < MIN_TREND_RUNS {
+        return None;
+    }
+    let window = window.clamp(MIN_TREND_RUNS, scores.len());
+    let recent = &scores[scores.len() - window..];
+    let best_index = scores
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(index, _)| index)
+        .unwrap_or(0);
+    let runs_since_best = scores.len() - 1 - best_index;
+    let rolling = ROLLING_WINDOW.min(scores.len());
+    let rolling_average = mean(&scores[scores.len() - rolling..]);
+    let previous_rolling_average = if scores.len() >= rolling * 2 {
+        Some(mean(&scores[scores.len() - rolling * 2..scores.len() - rolling]))
+    } else {
+        None
+    };
+    let window_average = mean(recent);
+    let slope_per_run = linear_slope(recent);
+    let mut trend = ScoreTrend {
+        total_runs: scores.len(),
+        window,
+        best_score: scores[best_index],
+        runs_since_best,
+        rolling_average,
+        previous_rolling_average,
+        window_average,
+        percentiles: percentiles(recent),
+        slope_per_run,
+        status: TrendStatus::Steady,
+    };
+    let change = trend.change_percent();
+    trend.status = if change >= FLAT_THRESHOLD_PERCENT {
+        TrendStatus::Improving
+    } else if change <= -FLAT_THRESHOLD_PERCENT {
+        TrendStatus::Declining
+    } else if window >= MIN_PLATEAU_RUNS && runs_since_best >= window {
+        TrendStatus::Plateau
+    } else {
+        TrendStatus::Steady
+    };
+    Some(trend)
+}
+/// Average of each run and the `window - 1` runs before it.
+pub fn rolling_averages(scores: &[f64], window: usize) -> Vec<f64> {
+    let window = window.max(1);
+    (0..scores.len())
+        .map(|index| mean(&scores[(index + 1).saturating_sub(window)..=index]))
+        .collect()
+}
+fn percentiles(scores: &[f64]) -> Percentiles {
+    let mut sorted = scores.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    Percentiles {
+        p10: percentile_sorted(&sorted, 10.0),
+        p25: percentile_sorted(&sorted, 25.0),
+        p50: percentile_sorted(&sorted, 50.0),
+        p75: percentile_sorted(&sorted, 75.0),
+        p90: percentile_sorted(&sorted, 90.0),
+    }
+}
+fn percentile_sorted(sorted: &[f64], percent: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = percent.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+fn mean(scores: &[f64]) -> f64 {
+    if scores.is_empty() {
+        return 0.0;
+    }
+    scores.iter().sum::<f64>() / scores.len() as f64
+}
+/// Least-squares slope of the scores against their run index.
+fn linear_slope(scores: &[f64]) -> f64 {
+    let n = scores.len() as f64;
+    if scores.len() < 2 {
+        return 0.0;
+    }
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = mean(scores);
+    let mut numerator = 0.0;
+    let mut denominator = 0.0;
+    for (index, score) in scores.iter().enumerate() {
+        let dx = index as f64 - mean_x;
+        numerator += dx * (score - mean_y);
+        denominator += dx * dx;
+    }
+    if denominator == 0.0 {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
+/// Plain-text report used by the CLI.
+pub fn format_trend_report(scenario: &str, trend: &ScoreTrend) -> String {
+    let mut lines = vec![
+        format!("{} ({} runs)", scenario, trend.total_runs),
+        format!("  Status:           {}", trend.status.label()),
+        format!(
+            "  Best:             {:.1} ({} runs ago)",
+            trend.best_score, trend.runs_since_best
+        ),
+    ];
+    let mut rolling = format!("  Rolling avg ({}):  {:.1}", ROLLING_WINDOW, trend.rolling_average);
+    if let Some(previous) = trend.previous_rolling_average {
+        rolling.push_str(&format!(" (previous {:.1})", previous));
+    }
+    lines.push(rolling);
+    lines.push(format!(
+        "  Trend (last {}):  {:+.2} per run ({:+.1}%)",
+        trend.window,
+        trend.slope_per_run,
+        trend.change_percent()
+    ));
+    let p = trend.percentiles;
+    lines.push(format!(
+        "  Percentiles:      p10 {:.1} · p25 {:.1} · p50 {:.1} · p75 {:.1} · p90 {:.1}",
+        p.p10, p.p25, p.p50, p.p75, p.p90
+    ));
+    lines.join("\n")
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::stats_filename::RunMode;
+    fn runs(scores: &[f64], mode: Option<RunMode>) -> Vec<RunRecord> {
+        scores
+            .iter()
+            .enumerate()
+            .map(|(index, score)| RunRecord {
+                played_at: index as u64,
+                score: *score,
+                kills: 0,
+                accuracy: None,
+                avg_ttk: None,
+                sens_scale: None,
+                horizontal_sens: None,
+                fov: None,
+                cm_per_360: None,
+                mode,
+            })
+            .collect()
+    }
+    #[test]
+    fn needs_three_runs() {
+        assert!(analyze_trend(&runs(&[10.0, 20.0], None), 20, CountedModes::Both).is_none());
+        assert!(analyze_trend(&runs(&[10.0, 20.0, 30.0], None), 20, CountedModes::Both).is_some());
+    }
+    #[test]
+    fn rising_scores_are_improving() {
+        let scores: Vec<f64> = (1..=10).map(|n| n as f64 * 10.0).collect();
+        let trend = analyze_trend(&runs(&scores, None), 20, CountedModes::Both).unwrap();
+        assert_eq!(trend.status, TrendStatus::Improving);
+        assert_eq!(trend.window, 10);
+        assert_eq!(trend.best_score, 100.0);
+        assert_eq!(trend.runs_since_best, 0);
+        assert!((trend.slope_per_run - 10.0).abs() < 1e-9);
+        assert_eq!(trend.rolling_average, 80.0);
+        assert_eq!(trend.previous_rolling_average, Some(30.0));
+        assert_eq!(trend.percentiles.p50, 55.0);
+        let falling: Vec<f64> = scores.into_iter().rev().collect();
+        let trend = analyze_trend(&runs(&falling, None), 20, CountedModes::Both).unwrap();
+        assert_eq!(trend.status, TrendStatus::Declining);
+    }
+    #[test]
+    fn flat_scores_below_an_old_best_are_a_plateau() {
+        let mut scores = vec![150.0];
+        scores.extend([100.0; 12]);
+        let trend = analyze_trend(&runs(&scores, None), 10, CountedModes::Both).unwrap();
+        assert_eq!(trend.status, TrendStatus::Plateau);
+        assert_eq!(trend.runs_since_best, 12);
+        let short = analyze_trend(&runs(&scores[1..6], None), 10, CountedModes::Both).unwrap();
+        assert_eq!(short.status, TrendStatus::Steady);
+    }
+    #[test]
+    fn only_counted_modes_are_analyzed() {
+        let mut mixed = runs(&[10.0, 20.0, 30.0, 40.0], Some(RunMode::Challenge));
+        mixed.extend(runs(&[500.0, 400.0], Some(RunMode::Freeplay)));
+        mixed.extend(runs(&[1000.0], None));
+        let challenge = analyze_trend(&mixed, 20, CountedModes::ChallengeOnly).unwrap();
+        assert_eq!(challenge.total_runs, 4);
+        assert_eq!(challenge.best_score, 40.0);
+        assert_eq!(challenge.status, TrendStatus::Improving);
+        assert!(analyze_trend(&mixed, 20, CountedModes::FreeplayOnly).is_none());
+        assert_eq!(analyze_trend(&mixed, 20, CountedModes::Both).unwrap().total_runs, 7);
+    }
+}
\ No newline at end of file
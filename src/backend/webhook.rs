@@ -0,0 +1,358 @@
+//! Posting personal bests and session summaries to a Discord webhook.
+use crate::backend::session_summary::{format_duration, SessionSummary};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+/// Gap kept between two posts, well under Discord's webhook limit.
+pub const MIN_POST_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_ATTEMPTS: u32 = 8;
+const BASE_RETRY_SECS: u64 = 30;
+const MAX_RETRY_SECS: u64 = 3600;
+const MAX_SESSION_FIELDS: usize = 10;
+const EMBED_COLOR: u32 = 0x10B981;
+/// A payload waiting to be posted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedPost {
+    pub payload: Value,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub next_attempt_at: u64,
+}
+/// What happened to a post.
+#[derive(Debug)]
+pub enum PostOutcome {
+    Sent,
+    RateLimited(Duration),
+    Retry(String),
+    Rejected(String),
+}
+/// Posts waiting to go out, persisted so they survive restarts and network outages.
+#[derive(Debug)]
+pub struct WebhookQueue {
+    file_path: PathBuf,
+    posts: Vec<QueuedPost>,
+    /// Unix time before which nothing is sent, set when Discord rate limits the webhook.
+    retry_after: u64,
+}
+impl WebhookQueue {
+    pub fn new() -> Result<Self> {
+        let app_data_dir = crate::backend::get_app_data_dir();
+        let _ = fs::create_dir_all(&app_data_dir);
+        Ok(Self::open(app_data_dir.join("webhook_queue.json")))
+    }
+    /// Queue stored in `file_path`, posts already in it are loaded.
+    fn open(file_path: PathBuf) -> Self {
+        let mut queue = Self {
+            file_path,
+            posts: Vec::new(),
+            retry_after: 0,
+        };
+        let _ = queue.load();
+        queue
+    }
+    fn load(&mut self) -> Result<()> {
+        if !self.file_path.exists() {
+            return Ok(());
+        }
+        let contents = fs::read_to_string(&self.file_path)?;
+        if contents.trim().is_empty() {
+            return Ok(());
+        }
+        self.posts = serde_json::from_str(&contents)?;
+        Ok(())
+    }
+    pub fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.posts)?;
+        let mut tmp = self.file_path.clone();
+        tmp.set_extension("tmp");
+        fs::write(&tmp, &json)?;
+        fs::rename(&tmp, &self.file_path)?;
+        Ok(())
+    }
+    pub fn push(&mut self, payload: Value) -> Result<()> {
+        self.posts.push(QueuedPost {
+            payload,
+            attempts: 0,
+            next_attempt_at: 0,
+        });
+        self.save()
+    }
+    /// Index and payload of the oldest post that is due, `None` while rate limited.
+    pub fn next_due(&self) -> Option<(usize, Value)> {
+        let now = unix_now();
+        if now < self.retry_after {
+            return None;
+        }
+        self.posts
+            .iter()
+            .position(|post| post.next_attempt_at <= now)
+            .map(|index| (index, self.posts[index].payload.clone()))
+    }
+    /// Applies the outcome of posting the post at `index`.
+    pub fn complete(&mut self, index: usize, outcome: &PostOutcome) -> Result<()> {
+        if index >= self.posts.len() {
+            return Ok(());
+        }
+        let now = unix_now();
+        match outcome {
+            PostOutcome::Sent | PostOutcome::Rejected(_) => {
+                self.posts.remove(index);
+            }
+            PostOutcome::RateLimited(retry_after) => {
+                self.retry_after = now + (retry_after.as_secs_f64().ceil() as u64).max(1);
+            }
+            PostOutcome::Retry(_) => {
+                let post = &mut self.posts[index];
+                post.attempts += 1;
+                if post.attempts >= MAX_ATTEMPTS {
+                    self.posts.remove(index);
+                } else {
+                    let backoff = BASE_RETRY_SECS.saturating_mul(1 << (post.attempts - 1));
+                    post.next_attempt_at = now + backoff.min(MAX_RETRY_SECS);
+                }
+            }
+        }
+        self.save()
+    }
+}
+/// Sends one payload, 429s and server errors are retried, other errors dropped.
+pub fn post_webhook(url: &str, payload: &Value) -> PostOutcome {
+    let client = reqwest::blocking::Client::new();
+    let response = match client
+        .post(url)
+        .json(payload)
+        .timeout(Duration::from_secs(10))
+        .send()
+    {
+        Ok(response) => response,
+        Err(e) => return PostOutcome::Retry(e.to_string()),
+    };
+    let status = response.status();
+    if status.is_success() {
+        return PostOutcome::Sent;
+    }
+    if status.as_u16() == 429 {
+        let header_secs = response
+            .headers()
+            .get("retry-after")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<f64>().ok());
+        let body_secs = response
+            .json::<Value>()
+            .ok()
+            .and_then(|body| body.get("retry_after").and_then(Value::as_f64));
+        let secs = body_secs.or(header_secs).unwrap_or(5.0);
+        return PostOutcome::RateLimited(Duration::from_secs_f64(secs.max(1.0)));
+    }
+    if status.is_server_error() {
+        return PostOutcome::Retry(format!("Webhook returned {}", status));
+    }
+    PostOutcome::Rejected(format!("Webhook returned {}", status))
+}
+/// Whether a new highscore gets a PB post, a first score on a scenario has no PB to beat and doesn't.
+pub fn beats_previous_best(old_score: f64, new_score: f64) -> bool {
+    old_score > 0.0 && new_score > old_score
+}
+/// Embed for a new personal best, without any score fields when `hide_scores` is set.
+pub fn personal_best_payload(
+    scenario: &str,
+    old_score: f64,
+    new_score: f64,
+    accuracy: Option<f64>,
+    hide_scores: bool,
+) -> Value {
+    let delta = new_score - old_score;
+    let mut fields = Vec::new();
+    if !hide_scores {
+        fields.push(json!({ "name": "Score", "value": format!("{:.1} → **{:.1}**", old_score, new_score), "inline": true }));
+        fields.push(json!({ "name": "Delta", "value": format_delta(delta, old_score), "inline": true }));
+        if let Some(accuracy) = accuracy {
+            fields.push(json!({ "name": "Accuracy", "value": format!("{:.1}%", accuracy), "inline": true }));
+        }
+    }
+    json!({
+        "embeds": [{
+            "title": format!("New PB: {}", scenario),
+            "color": EMBED_COLOR,
+            "fields": fields,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }]
+    })
+}
+/// Embed for a finished session, limited to the scenarios in `visible`, only run counts when `hide_scores` is set.
+pub fn session_payload(summary: &SessionSummary, visible: impl Fn(&str) -> bool, hide_scores: bool) -> Value {
+    let scenarios: Vec<_> = summary
+        .scenarios
+        .iter()
+        .filter(|scenario| visible(&scenario.scenario))
+        .collect();
+    let runs: usize = scenarios.iter().map(|scenario| scenario.runs).sum();
+    let pbs = scenarios.iter().filter(|scenario| scenario.is_pb).count();
+    let fields: Vec<Value> = scenarios
+        .iter()
+        .take(MAX_SESSION_FIELDS)
+        .map(|scenario| {
+            let mut value = format!("{} runs", scenario.runs);
+            if !hide_scores {
+                value.push_str(&format!(
+                    " · best {:.1} · avg {:.1}",
+                    scenario.best_score, scenario.average_score
+                ));
+                if let Some(gain) = scenario.improvement() {
+                    value.push_str(&format!(" ({:+.1}% vs. avg)", gain));
+                }
+                if let (true, Some(previous)) = (scenario.is_pb, scenario.previous_best) {
+                    value.push_str(&format!("\nPB {:.1} → {:.1}", previous, scenario.best_score));
+                }
+            }
+            json!({
+                "name": if scenario.is_pb { format!("{} 🏆", scenario.scenario) } else { scenario.scenario.clone() },
+                "value": value,
+                "inline": false,
+            })
+        })
+        .collect();
+    json!({
+        "embeds": [{
+            "title": "Session summary",
+            "description": format!(
+                "{} played, {} runs of {} scenarios, {} new PB{}",
+                format_duration(summary.active_secs),
+                runs,
+                scenarios.len(),
+                pbs,
+                if pbs == 1 { "" } else { "s" }
+            ),
+            "color": EMBED_COLOR,
+            "fields": fields,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }]
+    })
+}
+fn format_delta(delta: f64, old_score: f64) -> String {
+    if old_score > 0.0 {
+        format!("+{:.1} ({:+.1}%)", delta, delta / old_score * 100.0)
+    } else {
+        format!("+{:.1}", delta)
+    }
+}
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    /// Answers one request on a local port with `status` and `body`, returns the URL to post to.
+    fn serve_once(status: &str, body: &'static str) -> (String, thread::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/webhook", listener.local_addr().unwrap());
+        let status = status.to_string();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let read = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..read]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some(header_end) = text.find("\r\n\r\n") {
+                    let content_length = text[..header_end]
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().ok())?
+                        })
+                        .unwrap_or(0);
+                    if request.len() >= header_end + 4 + content_length {
+                        break;
+                    }
+                }
+                if read == 0 {
+                    break;
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        (url, handle)
+    }
+    fn post_to(status: &str, body: &'static str) -> PostOutcome {
+        let (url, server) = serve_once(status, body);
+        let outcome = post_webhook(&url, &json!({ "content": "test" }));
+        server.join().unwrap();
+        outcome
+    }
+    #[test]
+    fn success_is_sent() {
+        assert!(matches!(post_to("204 No Content", ""), PostOutcome::Sent));
+    }
+    #[test]
+    fn too_many_requests_waits_for_retry_after() {
+        let outcome = post_to("429 Too Many Requests", r#"{"retry_after": 7.5}"#);
+        match outcome {
+            PostOutcome::RateLimited(wait) => assert_eq!(wait, Duration::from_millis(7500)),
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+    }
+    #[test]
+    fn server_error_is_retried() {
+        assert!(matches!(post_to("503 Service Unavailable", ""), PostOutcome::Retry(_)));
+    }
+    #[test]
+    fn queue_survives_a_reload() {
+        let dir = std::env::temp_dir().join(format!("kovaaks_webhook_queue_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("webhook_queue.json");
+        let _ = fs::remove_file(&file_path);
+        let mut queue = WebhookQueue::open(file_path.clone());
+        queue.push(json!({ "content": "first" })).unwrap();
+        queue.push(json!({ "content": "second" })).unwrap();
+        queue
+            .complete(0, &PostOutcome::Retry("offline".to_string()))
+            .unwrap();
+        let reloaded = WebhookQueue::open(file_path);
+        assert_eq!(reloaded.posts.len(), 2);
+        assert_eq!(reloaded.posts[0].attempts, 1);
+        assert_eq!(reloaded.next_due(), Some((1, json!({ "content": "second" }))));
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn rate_limit_holds_back_every_post() {
+        let dir = std::env::temp_dir().join(format!("kovaaks_webhook_limit_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut queue = WebhookQueue::open(dir.join("webhook_queue.json"));
+        queue.push(json!({ "content": "first" })).unwrap();
+        queue.push(json!({ "content": "second" })).unwrap();
+        queue
+            .complete(0, &PostOutcome::RateLimited(Duration::from_millis(1500)))
+            .unwrap();
+        assert_eq!(queue.posts.len(), 2);
+        assert_eq!(queue.next_due(), None);
+        queue.retry_after = unix_now();
+        assert_eq!(queue.next_due(), Some((0, json!({ "content": "first" }))));
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn only_beaten_scores_are_posted_as_pbs() {
+        assert!(beats_previous_best(80.0, 80.5));
+        assert!(!beats_previous_best(80.0, 80.0));
+        assert!(!beats_previous_best(80.0, 75.0));
+        assert!(!beats_previous_best(0.0, 50.0));
+    }
+}
\ No newline at end of file
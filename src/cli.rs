@@ -0,0 +1,132 @@
+//! Command line commands, run instead of the app when arguments are given.
+use crate::backend::{
+    active_profile, analyze_trend, format_diagnostics, format_trend_report, load_settings,
+    run_diagnostics, scenario_key, CheckStatus, RunHistoryManager, DEFAULT_TREND_WINDOW,
+};
+const USAGE: &str = "Usage:
+  KovaaksDiscordRPC                          Start the app
+  KovaaksDiscordRPC trend [SCENARIO] [--last N]
+                                             Score trend of a scenario, or of every scenario
+  KovaaksDiscordRPC doctor                   Check paths, game files, Discord and the data files
+  KovaaksDiscordRPC help                     Show this message";
+/// Runs the command in `args` (without the program name), `None` when there is nothing to run.
+pub fn run(args: &[String]) -> Option<i32> {
+    let command = args.first()?;
+    attach_console();
+    let code = match command.as_str() {
+        "trend" => trend_command(&args[1..]),
+        "doctor" => doctor_command(),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            0
+        }
+        other => {
+            eprintln!("Unknown command '{}'\n\n{}", other, USAGE);
+            2
+        }
+    };
+    Some(code)
+}
+fn trend_command(args: &[String]) -> i32 {
+    let mut scenario: Option<String> = None;
+    let mut window = DEFAULT_TREND_WINDOW;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--last" || arg == "-n" {
+            match iter.next().and_then(|value| value.parse::<usize>().ok()) {
+                Some(value) if value > 0 => window = value,
+                _ => {
+                    eprintln!("--last needs a number of runs");
+                    return 2;
+                }
+            }
+        } else if scenario.is_none() {
+            scenario = Some(arg.clone());
+        } else {
+            eprintln!("Unexpected argument '{}'\n\n{}", arg, USAGE);
+            return 2;
+        }
+    }
+    let history = match RunHistoryManager::new() {
+        Ok(history) => history,
+        Err(e) => {
+            eprintln!("Could not open the run history: {}", e);
+            return 1;
+        }
+    };
+    let counted = load_settings().unwrap_or_default().counted_modes;
+    let scenarios = history.scenarios().unwrap_or_default();
+    let names: Vec<String> = match scenario {
+        Some(wanted) => {
+            let key = scenario_key(&wanted);
+            let found = scenarios.iter().find(|name| scenario_key(name) == key);
+            match found {
+                Some(name) => vec![name.clone()],
+                None => {
+                    eprintln!("No runs recorded for '{}'", wanted);
+                    return 1;
+                }
+            }
+        }
+        None => scenarios,
+    };
+    if names.is_empty() {
+        println!("No runs recorded yet, play a scenario with RPC running");
+        return 0;
+    }
+    let mut printed = 0;
+    for name in &names {
+        let runs: Vec<_> = history
+            .get_runs(name)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|run| counted.counts_run(run.mode))
+            .collect();
+        match analyze_trend(&runs, window, counted) {
+            Some(trend) => {
+                if printed > 0 {
+                    println!();
+                }
+                println!("{}", format_trend_report(name, &trend));
+                printed += 1;
+            }
+            None if names.len() == 1 => {
+                println!(
+                    "{}: {} runs recorded ({}), at least 3 are needed for a trend",
+                    name,
+                    runs.len(),
+                    counted.label()
+                );
+            }
+            None => {}
+        }
+    }
+    0
+}
+fn doctor_command() -> i32 {
+    let settings = match load_settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            println!("[{}] Settings: settings.json can't be read: {}", CheckStatus::Fail.label(), e);
+            Default::default()
+        }
+    };
+    let checks = run_diagnostics(&settings);
+    println!("Profile: {}", active_profile());
+    println!("{}", format_diagnostics(&checks));
+    if checks.iter().any(|check| check.status == CheckStatus::Fail) {
+        1
+    } else {
+        0
+    }
+}
+/// Release builds use the windows subsystem, so output needs the parent's console.
+#[cfg(windows)]
+fn attach_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+#[cfg(not(windows))]
+fn attach_console() {}
\ No newline at end of file
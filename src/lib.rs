@@ -0,0 +1,14 @@
+//! Discord Rich Presence for KovaaK's FPS Aim Trainer.
+//!
+//! The [`backend`] module is the reusable part of this crate: locating the
+//! game's files, parsing stats CSVs, the local score store, the KovaaK's
+//! webapp client and building presence content. The GUI and tray app are
+//! only compiled with the `gui` feature.
+pub mod backend;
+pub mod cli;
+pub mod state;
+pub mod workers;
+#[cfg(feature = "gui")]
+pub mod app;
+#[cfg(feature = "gui")]
+pub mod ui;
\ No newline at end of file
@@ -1,38 +1,142 @@
-#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
-mod app;
-mod backend;
-mod state;
-mod ui;
-mod workers;
-use app::KovaaksApp;
-use backend::{initialize_installation_path, is_kovaaks_running, load_settings, DiscordRPC};
+#![cfg_attr(all(feature = "gui", not(debug_assertions)), windows_subsystem = "windows")]
+#[cfg(feature = "gui")]
 use eframe::egui;
-use state::{create_tray_channel, create_ui_channel, AppState, TrayMessage};
-use std::sync::atomic::Ordering;
+#[cfg(feature = "gui")]
+use kovaaks_discord_rpc::app::KovaaksApp;
+use kovaaks_discord_rpc::backend::{
+    initialize_installation_path, load_active_profile, load_settings, set_scenario_aliases,
+};
+#[cfg(feature = "gui")]
+use kovaaks_discord_rpc::backend::{is_kovaaks_running, Settings};
+#[cfg(feature = "tray")]
+use kovaaks_discord_rpc::app::fill_profile_menu;
+#[cfg(feature = "tray")]
+use kovaaks_discord_rpc::backend::{ProfilesFile, DEFAULT_PROFILE};
+use kovaaks_discord_rpc::state::{create_ui_channel, AppState, UiUpdate};
+#[cfg(feature = "gui")]
+use kovaaks_discord_rpc::state::create_tray_channel;
+#[cfg(feature = "tray")]
+use kovaaks_discord_rpc::state::TrayMessage;
+use kovaaks_discord_rpc::workers;
+use std::sync::mpsc::Receiver;
 use std::sync::Arc;
+#[cfg(feature = "tray")]
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
     TrayIconBuilder, TrayIconEvent,
 };
 fn main() {
 
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
+    let profile = load_active_profile();
+    log::info!("Using profile {}", profile);
     let mut settings = load_settings().unwrap_or_default();
+    set_scenario_aliases(&settings.scenario_aliases);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = kovaaks_discord_rpc::cli::run(&args) {
+        std::process::exit(code);
+    }
+
     let _ = initialize_installation_path(&mut settings);
 
     let (ui_tx, ui_rx) = create_ui_channel();
-    let (tray_tx, tray_rx) = create_tray_channel();
-    let (shutdown_tx, shutdown_rx) = std::sync::mpsc::channel::<()>();
 
     let app_state = match AppState::new(settings.clone(), ui_tx) {
         Ok(state) => Arc::new(state),
         Err(_) => return,
     };
 
+    #[cfg(feature = "online")]
+    workers::start_webhook_thread(app_state.clone());
+
+    let state = app_state.clone();
+    app_state.track_worker(std::thread::spawn(move || state.import_missed_runs()));
+
+    #[cfg(feature = "gui")]
+    run_gui(settings, app_state, ui_rx);
+    #[cfg(not(feature = "gui"))]
+    run_headless(app_state, ui_rx);
+}
+#[cfg(not(feature = "gui"))]
+fn run_headless(app_state: Arc<AppState>, ui_rx: Receiver<UiUpdate>) {
+    log::info!("Running without GUI");
+    std::thread::spawn(move || {
+        for update in ui_rx {
+            match update {
+                UiUpdate::Toast { message } | UiUpdate::SyncComplete { message, .. } => {
+                    log::info!("{}", message);
+                }
+                UiUpdate::SessionEnded { summary } => {
+                    log::info!("{}", summary.headline());
+                }
+                _ => {}
+            }
+        }
+    });
+    loop {
+        if !app_state.is_rpc_running() {
+            if let Err(e) = workers::start_rpc(app_state.clone()) {
+                log::warn!("{}", e);
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_secs(30));
+    }
+}
+#[cfg(feature = "gui")]
+fn run_gui(settings: Settings, app_state: Arc<AppState>, ui_rx: Receiver<UiUpdate>) {
+    let (tray_tx, tray_rx) = create_tray_channel();
+    #[cfg(feature = "tray")]
+    let tray = spawn_tray(tray_tx, &app_state.profiles.load().unwrap_or_default());
+    #[cfg(not(feature = "tray"))]
+    drop(tray_tx);
+
+    if !settings.open_manually && is_kovaaks_running() {
+
+        let state = app_state.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_secs(2));
+            let _ = workers::start_rpc(state);
+        });
+    }
+
+    let start_visible = !settings.start_in_tray;
+
+    let native_options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([800.0, 600.0])
+            .with_min_inner_size([600.0, 450.0])
+            .with_title("Kovaaks Discord RPC")
+            .with_visible(start_visible),
+        ..Default::default()
+    };
+
+    let _ = eframe::run_native(
+        "Kovaaks Discord RPC",
+        native_options,
+        Box::new(move |cc| {
+            let app = KovaaksApp::new(cc, app_state, tray_rx, ui_rx);
+            #[cfg(feature = "tray")]
+            let app = app.with_tray(tray);
+            Ok(Box::new(app))
+        }),
+    );
+}
+#[cfg(feature = "tray")]
+fn spawn_tray(
+    tray_tx: std::sync::mpsc::Sender<TrayMessage>,
+    profiles: &ProfilesFile,
+) -> kovaaks_discord_rpc::app::TrayHandle {
+    let (shutdown_tx, shutdown_rx) = std::sync::mpsc::channel::<()>();
+
     let menu = Menu::new();
     let show_item = MenuItem::new("Show Window", true, None);
     let separator1 = PredefinedMenuItem::separator();
+    let active_profile = profiles.active_name();
+    let profile_menu = Submenu::new("Profile", true);
+    let profile_ids = Arc::new(parking_lot::Mutex::new(fill_profile_menu(&profile_menu, profiles)));
+    let separator_profiles = PredefinedMenuItem::separator();
     let start_item = MenuItem::new("Start RPC", true, None);
     let stop_item = MenuItem::new("Stop RPC", true, None);
     let separator2 = PredefinedMenuItem::separator();
@@ -40,6 +144,8 @@ fn main() {
     menu.append_items(&[
         &show_item,
         &separator1,
+        &profile_menu,
+        &separator_profiles,
         &start_item,
         &stop_item,
         &separator2,
@@ -49,9 +155,14 @@ fn main() {
 
     let icon = load_tray_icon();
 
+    let tooltip = if active_profile == DEFAULT_PROFILE {
+        "Kovaaks Discord RPC".to_string()
+    } else {
+        format!("Kovaaks Discord RPC ({})", active_profile)
+    };
     let tray_icon = TrayIconBuilder::new()
         .with_menu(Box::new(menu))
-        .with_tooltip("Kovaaks Discord RPC")
+        .with_tooltip(tooltip)
         .with_icon(icon)
         .build()
         .expect("Failed to create tray icon");
@@ -61,6 +172,7 @@ fn main() {
     let start_id = start_item.id().clone();
     let stop_id = stop_item.id().clone();
     let quit_id = quit_item.id().clone();
+    let thread_profile_ids = profile_ids.clone();
     let tray_thread_handle = std::thread::spawn(move || {
         let menu_channel = MenuEvent::receiver();
         let tray_channel = TrayIconEvent::receiver();
@@ -116,9 +228,15 @@ fn main() {
                         let _ = tray_tx_clone.send(TrayMessage::StartRpc);
                     } else if event.id == stop_id {
                         let _ = tray_tx_clone.send(TrayMessage::StopRpc);
+                    } else if let Some(name) = thread_profile_ids
+                        .lock()
+                        .iter()
+                        .find(|(id, _)| *id == event.id)
+                        .map(|(_, name)| name.clone())
+                    {
+                        let _ = tray_tx_clone.send(TrayMessage::SwitchProfile(name));
                     } else if event.id == quit_id {
-                        std::process::exit(0);
-                        break;
+                        let _ = tray_tx_clone.send(TrayMessage::Quit);
                     }
                 }
                 Err(_) => continue,
@@ -126,40 +244,15 @@ fn main() {
         }
     });
 
-    if !settings.open_manually && is_kovaaks_running() {
-
-        let state = app_state.clone();
-        std::thread::spawn(move || {
-            std::thread::sleep(std::time::Duration::from_secs(2));
-            if let Ok(mut rpc) = DiscordRPC::new() {
-                if rpc.connect().is_ok() {
-                    *state.rpc.lock() = Some(rpc);
-                    state.rpc_running.store(true, Ordering::Relaxed);
-                    *state.start_time.lock() = Some(chrono::Utc::now().timestamp());
-                    *state.session_start_time.lock() = std::time::SystemTime::now();
-                    workers::start_monitoring_thread(state);
-                }
-            }
-        });
+    kovaaks_discord_rpc::app::TrayHandle {
+        icon: tray_icon,
+        profile_menu,
+        profile_ids,
+        shutdown_tx,
+        thread: tray_thread_handle,
     }
-
-    let start_visible = !settings.start_in_tray;
-
-    let native_options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([800.0, 600.0])
-            .with_min_inner_size([600.0, 450.0])
-            .with_title("Kovaaks Discord RPC")
-            .with_visible(start_visible),
-        ..Default::default()
-    };
-
-    let _ = eframe::run_native(
-        "Kovaaks Discord RPC",
-        native_options,
-        Box::new(move |cc| Ok(Box::new(KovaaksApp::new(cc, app_state, tray_rx, ui_rx, tray_icon, shutdown_tx, tray_thread_handle)))),
-    );
 }
+#[cfg(feature = "tray")]
 fn load_tray_icon() -> tray_icon::Icon {
     let icon_data = include_bytes!("../assets/icon.png");
 
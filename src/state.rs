@@ -1,21 +1,32 @@
+//! Shared state between the GUI, the tray and the monitoring worker.
 use crate::backend::{
-    config::Settings, DiscordRPC, LocalScoresManager, OnlineScoreAPI, ScenarioValidationCache,
-    ScenarioScore,
+    config::Settings, get_stats_directory, is_scenario_listed_visible, scenario_key,
+    CatchUpManager, DiscordRPC, LocalScoresManager, ProfileManager, RunHistoryManager, RunMode,
+    RunStats, ScenarioScore, ScenarioValidationCache, SessionLogManager, SessionSummary,
+    StatsIndexManager, TrainingCalendar,
 };
+#[cfg(feature = "online")]
+use crate::backend::{
+    beats_previous_best, personal_best_payload, session_payload, OnlineScoreAPI, WebhookQueue,
+};
+use crate::workers::SessionState;
 use parking_lot::Mutex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
-use std::time::SystemTime;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum UiUpdate {
     RpcStateChanged { running: bool },
+    SessionStateChanged { state: SessionState },
     ScenarioChanged { name: String, highscore: f64, session_best: f64 },
     ScoresUpdated,
     SyncProgress { message: String },
     SyncComplete { success: bool, message: String },
+    SessionEnded { summary: SessionSummary },
     Toast { message: String },
 }
 
@@ -24,67 +35,139 @@ pub enum TrayMessage {
     Show,
     StartRpc,
     StopRpc,
+    SwitchProfile(String),
     Quit,
 }
 
 pub struct AppState {
 
     pub settings: Mutex<Settings>,
+    pub profiles: ProfileManager,
 
     pub rpc: Mutex<Option<DiscordRPC>>,
     pub rpc_running: AtomicBool,
     pub current_scenario: Mutex<String>,
+    pub current_mode: Mutex<Option<RunMode>>,
     pub local_highscore: Mutex<f64>,
     pub session_highscore: Mutex<f64>,
     pub start_time: Mutex<Option<i64>>,
     pub checked_files: Mutex<Vec<String>>,
+    /// Stats files already reported as unreadable, so each is reported once.
+    reported_unreadable_files: Mutex<HashSet<String>>,
 
+    #[cfg(feature = "online")]
     pub online_api: OnlineScoreAPI,
     pub online_scores: Mutex<HashMap<String, f64>>,
 
     pub local_scores_manager: LocalScoresManager,
     pub score_cache: Mutex<HashMap<String, ScenarioScore>>,
+    pub run_history: RunHistoryManager,
+    pub catch_up: CatchUpManager,
+    pub catch_up_in_progress: AtomicBool,
+    pub stats_index: StatsIndexManager,
+    pub stats_scan_in_progress: AtomicBool,
+    pub stats_scan_cancel: AtomicBool,
+    pub session_log: SessionLogManager,
+    pub training_calendar: Mutex<Option<TrainingCalendar>>,
+    #[cfg(feature = "online")]
+    pub webhook_queue: Mutex<WebhookQueue>,
 
     pub session_start_time: Mutex<SystemTime>,
+    /// Session maps below are keyed by scenario key.
     pub session_best_scores: Mutex<HashMap<String, f64>>,
+    pub session_last_runs: Mutex<HashMap<String, RunStats>>,
+    pub session_start_highscores: Mutex<HashMap<String, f64>>,
     pub kovaaks_was_running: AtomicBool,
+    pub session_state: Mutex<SessionState>,
 
     pub scenario_validation_cache: Mutex<ScenarioValidationCache>,
 
     pub sync_in_progress: AtomicBool,
+    /// Set by [`AppState::stop_workers`], background loops exit at their next check.
+    pub shutting_down: AtomicBool,
+    /// Threads writing the data files, joined by [`AppState::stop_workers`].
+    worker_threads: Mutex<Vec<JoinHandle<()>>>,
 
     pub ui_update_tx: Sender<UiUpdate>,
 }
 impl AppState {
     pub fn new(settings: Settings, ui_update_tx: Sender<UiUpdate>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let profiles = ProfileManager::new()?;
         let local_scores_manager = LocalScoresManager::new()?;
         let scenario_validation_cache = ScenarioValidationCache::new()?;
+        let run_history = RunHistoryManager::new()?;
+        let catch_up = CatchUpManager::new()?;
+        let stats_index = StatsIndexManager::new()?;
+        let session_log = SessionLogManager::new()?;
+        #[cfg(feature = "online")]
+        let webhook_queue = WebhookQueue::new()?;
 
         let score_cache = local_scores_manager.get_all_scores().unwrap_or_default();
         Ok(Self {
             settings: Mutex::new(settings),
+            profiles,
             rpc: Mutex::new(None),
             rpc_running: AtomicBool::new(false),
             current_scenario: Mutex::new(String::new()),
+            current_mode: Mutex::new(None),
             local_highscore: Mutex::new(0.0),
             session_highscore: Mutex::new(0.0),
             start_time: Mutex::new(None),
             checked_files: Mutex::new(Vec::new()),
+            reported_unreadable_files: Mutex::new(HashSet::new()),
+            #[cfg(feature = "online")]
             online_api: OnlineScoreAPI::new(),
             online_scores: Mutex::new(HashMap::new()),
             local_scores_manager,
             score_cache: Mutex::new(score_cache),
+            run_history,
+            catch_up,
+            catch_up_in_progress: AtomicBool::new(false),
+            stats_index,
+            stats_scan_in_progress: AtomicBool::new(false),
+            stats_scan_cancel: AtomicBool::new(false),
+            session_log,
+            training_calendar: Mutex::new(None),
+            #[cfg(feature = "online")]
+            webhook_queue: Mutex::new(webhook_queue),
             session_start_time: Mutex::new(SystemTime::now()),
             session_best_scores: Mutex::new(HashMap::new()),
+            session_last_runs: Mutex::new(HashMap::new()),
+            session_start_highscores: Mutex::new(HashMap::new()),
             kovaaks_was_running: AtomicBool::new(false),
+            session_state: Mutex::new(SessionState::GameClosed),
             scenario_validation_cache: Mutex::new(scenario_validation_cache),
             sync_in_progress: AtomicBool::new(false),
+            shutting_down: AtomicBool::new(false),
+            worker_threads: Mutex::new(Vec::new()),
             ui_update_tx,
         })
     }
     pub fn is_rpc_running(&self) -> bool {
         self.rpc_running.load(Ordering::Relaxed)
     }
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::Relaxed)
+    }
+    /// Keeps a worker thread's handle so [`Self::stop_workers`] can wait for it.
+    pub fn track_worker(&self, handle: JoinHandle<()>) {
+        let mut threads = self.worker_threads.lock();
+        threads.retain(|thread| !thread.is_finished());
+        threads.push(handle);
+    }
+    /// Cancels the stats scan, stops monitoring and the webhook queue and waits for every worker to finish,
+    /// so nothing writes the data files any more.
+    pub fn stop_workers(&self) {
+        self.shutting_down.store(true, Ordering::Relaxed);
+        self.stats_scan_cancel.store(true, Ordering::Relaxed);
+        self.rpc_running.store(false, Ordering::Relaxed);
+        let threads = std::mem::take(&mut *self.worker_threads.lock());
+        for thread in threads {
+            if thread.join().is_err() {
+                log::warn!("A worker thread panicked while stopping");
+            }
+        }
+    }
     pub fn get_current_scenario(&self) -> String {
         self.current_scenario.lock().clone()
     }
@@ -94,15 +177,169 @@ impl AppState {
     pub fn get_session_highscore(&self) -> f64 {
         *self.session_highscore.lock()
     }
+    pub fn get_last_run(&self) -> Option<RunStats> {
+        let scenario = self.current_scenario.lock().clone();
+        self.session_last_runs.lock().get(&scenario_key(&scenario)).cloned()
+    }
+    pub fn get_session_state(&self) -> SessionState {
+        self.session_state.lock().clone()
+    }
+    pub fn reset_session(&self, started_at: SystemTime) {
+        *self.session_start_time.lock() = started_at;
+        self.session_best_scores.lock().clear();
+        self.session_last_runs.lock().clear();
+        self.session_start_highscores.lock().clear();
+        self.checked_files.lock().clear();
+    }
+    /// Summary of the session that just ended, `None` when no runs were recorded.
+    pub fn summarize_session(&self, active: Duration) -> Option<SessionSummary> {
+        let history = self.run_history.load().ok()?;
+        let started_at = unix_secs(*self.session_start_time.lock());
+        let ended_at = unix_secs(SystemTime::now());
+        SessionSummary::build(
+            &history,
+            started_at,
+            ended_at,
+            active.as_secs(),
+            &self.session_start_highscores.lock(),
+            self.settings.lock().counted_modes,
+        )
+    }
+    pub fn clear_current_scenario(&self) {
+        *self.current_scenario.lock() = String::new();
+        *self.current_mode.lock() = None;
+        *self.local_highscore.lock() = 0.0;
+        *self.session_highscore.lock() = 0.0;
+    }
     pub fn send_ui_update(&self, update: UiUpdate) {
         let _ = self.ui_update_tx.send(update);
     }
+    /// Logs and toasts stats files that couldn't be read, skipping ones reported before.
+    pub fn report_unreadable_files(&self, files: &[String]) {
+        let new_files: Vec<&String> = {
+            let mut reported = self.reported_unreadable_files.lock();
+            files.iter().filter(|file| reported.insert((*file).clone())).collect()
+        };
+        if new_files.is_empty() {
+            return;
+        }
+        log::warn!("Could not read stats files: {:?}", new_files);
+        let count = new_files.len();
+        self.send_ui_update(UiUpdate::Toast {
+            message: format!("{} stats file{} couldn't be read", count, if count == 1 { "" } else { "s" }),
+        });
+    }
+    /// Highscore of a scenario over the modes counted in the settings.
     pub fn get_score_for_scenario(&self, scenario_name: &str) -> f64 {
+        let counted = self.settings.lock().counted_modes;
+        let cache = self.score_cache.lock();
+        cache
+            .get(&scenario_key(scenario_name))
+            .map(|s| s.counted_highscore(counted))
+            .unwrap_or(0.0)
+    }
+    /// Online highscore of a scenario from the last sync, if it has one.
+    pub fn get_online_score_for_scenario(&self, scenario_name: &str) -> Option<f64> {
+        let cache = self.score_cache.lock();
+        cache
+            .get(&scenario_key(scenario_name))
+            .and_then(|s| s.online_highscore)
+    }
+    /// Re-reads the current scenario's highscore, e.g. after the counted modes changed.
+    pub fn refresh_current_highscore(&self) {
+        let scenario = self.current_scenario.lock().clone();
+        if scenario.is_empty() {
+            return;
+        }
+        let highscore = self.get_score_for_scenario(&scenario);
+        *self.local_highscore.lock() = highscore;
+    }
+    /// Imports runs played since the last import and reports them in the UI, skipped while another import runs.
+    pub fn import_missed_runs(&self) {
+        if self.catch_up_in_progress.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let settings = self.settings.lock().clone();
+        let stats_dir = get_stats_directory(&settings);
+        let result = self.catch_up.import_since_last_launch(
+            &stats_dir,
+            &self.local_scores_manager,
+            &self.run_history,
+            settings.mouse_dpi,
+        );
+        match result {
+            Ok(report) if report.runs > 0 || !report.unreadable_files.is_empty() => {
+                if let Ok(all_scores) = self.local_scores_manager.get_all_scores() {
+                    *self.score_cache.lock() = all_scores;
+                }
+                self.refresh_current_highscore();
+                self.refresh_calendar();
+                self.send_ui_update(UiUpdate::ScoresUpdated);
+                if report.runs > 0 {
+                    let message = format!(
+                        "Imported {} run{} since last launch",
+                        report.runs,
+                        if report.runs == 1 { "" } else { "s" }
+                    );
+                    log::info!("{}", message);
+                    self.send_ui_update(UiUpdate::Toast { message });
+                }
+                self.report_unreadable_files(&report.unreadable_files);
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Catch-up import failed: {}", e),
+        }
+        self.catch_up_in_progress.store(false, Ordering::Relaxed);
+    }
+    /// Rebuilds the training calendar from the stats index, bringing the index up to date
+    /// first unless a stats scan is already doing that.
+    pub fn refresh_calendar(&self) {
+        let stats_dir = get_stats_directory(&self.settings.lock());
+        let updated = if self.stats_scan_in_progress.swap(true, Ordering::Relaxed) {
+            self.stats_index.load()
+        } else {
+            self.stats_scan_cancel.store(false, Ordering::Relaxed);
+            let updated = self
+                .stats_index
+                .update(&stats_dir, &self.stats_scan_cancel, &|_, _| {})
+                .map(|update| update.index);
+            self.stats_scan_in_progress.store(false, Ordering::Relaxed);
+            updated
+        };
+        let index = updated.unwrap_or_else(|e| {
+            log::warn!("Could not update the stats index: {}", e);
+            self.stats_index.load().unwrap_or_default()
+        });
+        *self.training_calendar.lock() = Some(TrainingCalendar::from_index(&index));
+    }
+    /// Current training streak in days, 0 until the calendar has been built.
+    pub fn current_streak(&self) -> u32 {
+        self.training_calendar
+            .lock()
+            .as_ref()
+            .map(|calendar| calendar.current_streak(chrono::Local::now().date_naive()))
+            .unwrap_or(0)
+    }
+    pub fn get_goal_for_scenario(&self, scenario_name: &str) -> Option<f64> {
         let cache = self.score_cache.lock();
-        cache.get(scenario_name).map(|s| s.highscore).unwrap_or(0.0)
+        cache
+            .get(&scenario_key(scenario_name))
+            .and_then(|s| s.goal)
+            .filter(|goal| *goal > 0.0)
+    }
+    /// Sets a scenario's goal and refreshes the score cache.
+    pub fn set_goal(&self, scenario_name: &str, goal: Option<f64>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.local_scores_manager.set_goal(scenario_name, goal)?;
+        if let Ok(all_scores) = self.local_scores_manager.get_all_scores() {
+            *self.score_cache.lock() = all_scores;
+        }
+        Ok(())
     }
     pub fn is_scenario_allowed(&self, scenario_name: &str) -> bool {
         let settings = self.settings.lock();
+        if !is_scenario_listed_visible(&settings, scenario_name) {
+            return false;
+        }
         if !settings.online_only_scenarios {
             return true;
         }
@@ -113,25 +350,71 @@ impl AppState {
         }
 
         let online_scores = self.online_scores.lock();
-        if online_scores.contains_key(scenario_name) {
+        if online_scores.contains_key(&scenario_key(scenario_name)) {
             let _ = cache.insert(scenario_name, true);
             return true;
         }
 
-        if !settings.online_scores_synced {
+        if !settings.online_scores_synced || cache.recently_failed(scenario_name) {
             return true;
         }
 
-        let _username = settings.webapp_username.clone();
         drop(settings);
         drop(online_scores);
         drop(cache);
-        if let Ok(is_available) = self.online_api.search_scenario_popular(scenario_name) {
-            let mut cache = self.scenario_validation_cache.lock();
-            let _ = cache.insert(scenario_name, is_available);
-            return is_available;
+        match self.lookup_scenario_online(scenario_name) {
+            Ok(is_available) => {
+                let _ = self.scenario_validation_cache.lock().insert(scenario_name, is_available);
+                is_available
+            }
+            Err(_) => {
+
+                self.scenario_validation_cache.lock().record_failed_lookup(scenario_name);
+                true
+            }
         }
-        true
+    }
+    /// Queues a PB post, skipped for a first score on the scenario.
+    #[cfg(feature = "online")]
+    pub fn notify_personal_best(&self, scenario_name: &str, old_score: f64, new_score: f64, accuracy: Option<f64>) {
+        let settings = self.settings.lock();
+        if !beats_previous_best(old_score, new_score)
+            || settings.webhook_url.trim().is_empty()
+            || !settings.webhook_post_pbs
+            || settings.streamer_mode
+            || !is_scenario_listed_visible(&settings, scenario_name)
+        {
+            return;
+        }
+        let hide_scores = settings.hide_scores;
+        drop(settings);
+        let payload = personal_best_payload(scenario_name, old_score, new_score, accuracy, hide_scores);
+        let _ = self.webhook_queue.lock().push(payload);
+    }
+    #[cfg(not(feature = "online"))]
+    pub fn notify_personal_best(&self, _scenario_name: &str, _old_score: f64, _new_score: f64, _accuracy: Option<f64>) {}
+    #[cfg(feature = "online")]
+    pub fn notify_session_end(&self, summary: &SessionSummary) {
+        let settings = self.settings.lock().clone();
+        if settings.webhook_url.trim().is_empty() || !settings.webhook_post_sessions || settings.streamer_mode {
+            return;
+        }
+        let visible = |name: &str| is_scenario_listed_visible(&settings, name);
+        if !summary.scenarios.iter().any(|scenario| visible(&scenario.scenario)) {
+            return;
+        }
+        let payload = session_payload(summary, visible, settings.hide_scores);
+        let _ = self.webhook_queue.lock().push(payload);
+    }
+    #[cfg(not(feature = "online"))]
+    pub fn notify_session_end(&self, _summary: &SessionSummary) {}
+    #[cfg(feature = "online")]
+    fn lookup_scenario_online(&self, scenario_name: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        self.online_api.search_scenario_popular(scenario_name)
+    }
+    #[cfg(not(feature = "online"))]
+    fn lookup_scenario_online(&self, _scenario_name: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        Err("Online features are disabled in this build".into())
     }
 }
 
@@ -141,4 +424,9 @@ pub fn create_ui_channel() -> (Sender<UiUpdate>, Receiver<UiUpdate>) {
 
 pub fn create_tray_channel() -> (Sender<TrayMessage>, Receiver<TrayMessage>) {
     channel()
+}
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
\ No newline at end of file
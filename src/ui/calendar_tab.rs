@@ -0,0 +1,120 @@
+use crate::backend::{format_duration, TrainingCalendar};
+use crate::ui::theme::*;
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use eframe::egui::{self, Color32, RichText};
+const HEATMAP_WEEKS: i64 = 26;
+const CELL_SIZE: f32 = 14.0;
+const CELL_GAP: f32 = 3.0;
+#[derive(Default)]
+pub struct CalendarTabAction {
+    pub refresh: bool,
+}
+pub fn render_calendar_tab(ui: &mut egui::Ui, calendar: Option<&TrainingCalendar>, is_building: bool) -> CalendarTabAction {
+    let mut action = CalendarTabAction::default();
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        let max_width = 600.0;
+        ui.vertical_centered(|ui| {
+            ui.add_space(16.0);
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        section_header(ui, "Training Calendar");
+                        let Some(calendar) = calendar else {
+                            ui.label(RichText::new("Reading your stats folder...").size(13.0).color(TEXT_MUTED));
+                            return;
+                        };
+                        let today = Local::now().date_naive();
+                        ui.horizontal(|ui| {
+                            streak_label(ui, "Current streak", calendar.current_streak(today));
+                            ui.add_space(24.0);
+                            streak_label(ui, "Longest streak", calendar.longest_streak());
+                        });
+                        ui.add_space(12.0);
+                        render_heatmap(ui, calendar, today);
+                        ui.add_space(8.0);
+                        let today_stats = calendar.day(today);
+                        ui.label(RichText::new(format!(
+                            "Today: {} runs, {}",
+                            today_stats.runs,
+                            format_duration(today_stats.playtime_secs as u64)
+                        )).size(12.0).color(TEXT_MUTED));
+                        ui.add_space(8.0);
+                        ui.add_enabled_ui(!is_building, |ui| {
+                            let text = if is_building { "Refreshing..." } else { "Refresh" };
+                            if styled_button(ui, text, false).clicked() {
+                                action.refresh = true;
+                            }
+                        });
+                    });
+                },
+            );
+            ui.add_space(24.0);
+        });
+    });
+    action
+}
+fn streak_label(ui: &mut egui::Ui, label: &str, days: u32) {
+    ui.vertical(|ui| {
+        ui.label(RichText::new(label).size(12.0).color(TEXT_MUTED));
+        let unit = if days == 1 { "day" } else { "days" };
+        ui.label(RichText::new(format!("{} {}", days, unit)).size(18.0).color(TEXT_WHITE).strong());
+    });
+}
+/// Weeks as columns, Monday to Sunday as rows, shaded by runs per day.
+fn render_heatmap(ui: &mut egui::Ui, calendar: &TrainingCalendar, today: NaiveDate) {
+    let max_runs = calendar.max_runs_per_day().max(1);
+    let this_monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let first_monday = this_monday - Duration::weeks(HEATMAP_WEEKS - 1);
+    let size = egui::vec2(
+        HEATMAP_WEEKS as f32 * (CELL_SIZE + CELL_GAP),
+        7.0 * (CELL_SIZE + CELL_GAP),
+    );
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let hover_pos = response.hover_pos();
+    let mut hovered: Option<NaiveDate> = None;
+    for week in 0..HEATMAP_WEEKS {
+        for weekday in 0..7 {
+            let date = first_monday + Duration::days(week * 7 + weekday);
+            if date > today {
+                continue;
+            }
+            let min = rect.min
+                + egui::vec2(
+                    week as f32 * (CELL_SIZE + CELL_GAP),
+                    weekday as f32 * (CELL_SIZE + CELL_GAP),
+                );
+            let cell = egui::Rect::from_min_size(min, egui::vec2(CELL_SIZE, CELL_SIZE));
+            let runs = calendar.day(date).runs;
+            painter.rect_filled(cell, egui::Rounding::same(3.0), heat_color(runs, max_runs));
+            if hover_pos.is_some_and(|pos| cell.contains(pos)) {
+                hovered = Some(date);
+            }
+        }
+    }
+    if let Some(date) = hovered {
+        let day = calendar.day(date);
+        response.on_hover_text(format!(
+            "{}: {} runs, {}",
+            date.format("%a %Y-%m-%d"),
+            day.runs,
+            format_duration(day.playtime_secs as u64)
+        ));
+    }
+}
+fn heat_color(runs: u32, max_runs: u32) -> Color32 {
+    if runs == 0 {
+        return BORDER_SUBTLE;
+    }
+    let intensity = 0.25 + 0.75 * (runs as f32 / max_runs as f32);
+    Color32::from_rgba_unmultiplied(
+        STATUS_GREEN.r(),
+        STATUS_GREEN.g(),
+        STATUS_GREEN.b(),
+        (intensity * 255.0) as u8,
+    )
+}
\ No newline at end of file
@@ -0,0 +1,59 @@
+use crate::backend::{CheckStatus, DiagnosticCheck};
+use crate::ui::theme::*;
+use eframe::egui::{self, RichText};
+#[derive(Default)]
+pub struct DiagnosticsTabAction {
+    pub run: bool,
+}
+pub fn render_diagnostics_tab(ui: &mut egui::Ui, checks: Option<&[DiagnosticCheck]>, is_running: bool) -> DiagnosticsTabAction {
+    let mut action = DiagnosticsTabAction::default();
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        let max_width = 600.0;
+        ui.vertical_centered(|ui| {
+            ui.add_space(16.0);
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        section_header(ui, "Diagnostics");
+                        match checks {
+                            Some(checks) => {
+                                egui::Grid::new("diagnostics_grid")
+                                    .num_columns(3)
+                                    .spacing([16.0, 8.0])
+                                    .show(ui, |ui| {
+                                        for check in checks {
+                                            let color = match check.status {
+                                                CheckStatus::Pass => STATUS_GREEN,
+                                                CheckStatus::Warn => STATUS_YELLOW,
+                                                CheckStatus::Fail => STATUS_RED,
+                                            };
+                                            ui.label(RichText::new(check.status.label()).size(12.0).strong().color(color));
+                                            ui.label(RichText::new(check.name).size(13.0).color(TEXT_WHITE));
+                                            ui.label(RichText::new(&check.detail).size(12.0).color(TEXT_MUTED));
+                                            ui.end_row();
+                                        }
+                                    });
+                            }
+                            None => {
+                                ui.label(RichText::new("Checking...").size(13.0).color(TEXT_MUTED));
+                            }
+                        }
+                        ui.add_space(12.0);
+                        ui.add_enabled_ui(!is_running, |ui| {
+                            let text = if is_running { "Checking..." } else { "Run Again" };
+                            if styled_button(ui, text, false).clicked() {
+                                action.run = true;
+                            }
+                        });
+                    });
+                },
+            );
+            ui.add_space(24.0);
+        });
+    });
+    action
+}
\ No newline at end of file
@@ -0,0 +1,299 @@
+use crate::backend::{
+    analyze_trend, format_timestamp, rolling_averages, sensitivity_breakdown, CountedModes,
+    RunHistoryManager, RunRecord, ScoreTrend, TrendStatus, DEFAULT_TREND_WINDOW, ROLLING_WINDOW,
+};
+use crate::ui::theme::*;
+use eframe::egui::{self, RichText};
+const RECENT_RUNS: usize = 25;
+const CHART_RUNS: usize = 100;
+const CHART_HEIGHT: f32 = 140.0;
+/// Scenario picker and the runs loaded for it, reloaded from disk only when stale.
+pub struct HistoryView {
+    pub selected: Option<String>,
+    scenarios: Vec<String>,
+    runs: Vec<RunRecord>,
+    modes: CountedModes,
+    trend_window: usize,
+    loaded: bool,
+}
+impl Default for HistoryView {
+    fn default() -> Self {
+        Self {
+            selected: None,
+            scenarios: Vec::new(),
+            runs: Vec::new(),
+            modes: CountedModes::Both,
+            trend_window: DEFAULT_TREND_WINDOW,
+            loaded: false,
+        }
+    }
+}
+impl HistoryView {
+    pub fn mark_stale(&mut self) {
+        self.loaded = false;
+    }
+    fn refresh(&mut self, history: &RunHistoryManager) {
+        self.scenarios = history.scenarios().unwrap_or_default();
+        if self.selected.is_none() {
+            self.selected = self.scenarios.first().cloned();
+        }
+        let runs = match &self.selected {
+            Some(name) => history.get_runs(name).unwrap_or_default(),
+            None => Vec::new(),
+        };
+        let modes = self.modes;
+        self.runs = runs
+            .into_iter()
+            .filter(|run| modes.counts_run(run.mode))
+            .collect();
+        self.loaded = true;
+    }
+}
+pub fn render_history_tab(ui: &mut egui::Ui, view: &mut HistoryView, history: &RunHistoryManager) {
+    if !view.loaded {
+        view.refresh(history);
+    }
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        let max_width = 600.0;
+        ui.vertical_centered(|ui| {
+            ui.add_space(16.0);
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        section_header(ui, "Run History");
+                        if view.scenarios.is_empty() {
+                            ui.label(RichText::new("No runs recorded yet, play a scenario with RPC running").size(13.0).color(TEXT_MUTED));
+                            return;
+                        }
+                        let mut selected = view.selected.clone();
+                        egui::ComboBox::from_id_salt("history_scenario")
+                            .width(max_width - 40.0)
+                            .selected_text(selected.clone().unwrap_or_default())
+                            .show_ui(ui, |ui| {
+                                for name in &view.scenarios {
+                                    ui.selectable_value(&mut selected, Some(name.clone()), name);
+                                }
+                            });
+                        let mut modes = view.modes;
+                        ui.add_space(4.0);
+                        egui::ComboBox::from_id_salt("history_modes")
+                            .width(max_width - 40.0)
+                            .selected_text(modes.label())
+                            .show_ui(ui, |ui| {
+                                for counted in CountedModes::ALL {
+                                    ui.selectable_value(&mut modes, counted, counted.label());
+                                }
+                            });
+                        if selected != view.selected || modes != view.modes {
+                            view.selected = selected;
+                            view.modes = modes;
+                            view.refresh(history);
+                        }
+                        ui.add_space(4.0);
+                        ui.label(RichText::new(format!("{} runs recorded", view.runs.len())).size(11.0).color(TEXT_DISABLED));
+                    });
+                },
+            );
+            ui.add_space(20.0);
+
+            if view.runs.is_empty() {
+                return;
+            }
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        render_trend(ui, &view.runs, view.modes, &mut view.trend_window);
+                    });
+                },
+            );
+            ui.add_space(20.0);
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        render_sensitivity_breakdown(ui, &view.runs);
+                    });
+                },
+            );
+            ui.add_space(20.0);
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        render_recent_runs(ui, &view.runs);
+                    });
+                },
+            );
+            ui.add_space(24.0);
+        });
+    });
+}
+fn render_trend(ui: &mut egui::Ui, runs: &[RunRecord], modes: CountedModes, window: &mut usize) {
+    section_header(ui, "Trend");
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Last").size(13.0).color(TEXT_MUTED));
+        ui.add(egui::DragValue::new(window).range(3..=500).speed(1.0));
+        ui.label(RichText::new("runs").size(13.0).color(TEXT_MUTED));
+    });
+    ui.add_space(8.0);
+    let Some(trend) = analyze_trend(runs, *window, modes) else {
+        ui.label(RichText::new("At least 3 runs are needed for a trend").size(13.0).color(TEXT_MUTED));
+        return;
+    };
+    let status_color = match trend.status {
+        TrendStatus::Improving => STATUS_GREEN,
+        TrendStatus::Declining => STATUS_RED,
+        TrendStatus::Steady | TrendStatus::Plateau => TEXT_WHITE,
+    };
+    ui.label(RichText::new(trend.status.label()).size(18.0).color(status_color).strong());
+    if trend.status == TrendStatus::Plateau {
+        ui.label(RichText::new(format!(
+            "No new best in {} runs and the last {} are flat, try a variation or a break",
+            trend.runs_since_best, trend.window
+        )).size(12.0).color(TEXT_MUTED));
+    }
+    ui.add_space(8.0);
+    egui::Grid::new("trend_grid")
+        .num_columns(2)
+        .spacing([20.0, 6.0])
+        .show(ui, |ui| {
+            trend_row(ui, &format!("Rolling average ({})", ROLLING_WINDOW), match trend.previous_rolling_average {
+                Some(previous) => format!("{:.1} (previous {:.1})", trend.rolling_average, previous),
+                None => format!("{:.1}", trend.rolling_average),
+            });
+            trend_row(ui, &format!("Trend (last {})", trend.window), format!(
+                "{:+.2} per run ({:+.1}%)",
+                trend.slope_per_run,
+                trend.change_percent()
+            ));
+            let p = trend.percentiles;
+            trend_row(ui, "p25 / p50 / p75", format!("{:.1} / {:.1} / {:.1}", p.p25, p.p50, p.p75));
+            trend_row(ui, "p10 / p90", format!("{:.1} / {:.1}", p.p10, p.p90));
+            trend_row(ui, "Best", format!("{:.1} ({} runs ago)", trend.best_score, trend.runs_since_best));
+        });
+    ui.add_space(8.0);
+    render_trend_chart(ui, runs, &trend);
+}
+fn trend_row(ui: &mut egui::Ui, label: &str, value: String) {
+    ui.label(RichText::new(label).size(12.0).color(TEXT_MUTED));
+    ui.label(RichText::new(value).size(13.0).color(TEXT_WHITE));
+    ui.end_row();
+}
+/// Recent scores, their rolling average, the p25-p75 band and the trend line of the window.
+fn render_trend_chart(ui: &mut egui::Ui, runs: &[RunRecord], trend: &ScoreTrend) {
+    let scores: Vec<f64> = runs.iter().map(|run| run.score).collect();
+    let averages = rolling_averages(&scores, ROLLING_WINDOW);
+    let start = scores.len().saturating_sub(CHART_RUNS);
+    let scores = &scores[start..];
+    let averages = &averages[start..];
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), CHART_HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_stroke(rect, egui::Rounding::same(4.0), egui::Stroke::new(1.0, BORDER_SUBTLE));
+    let min = scores.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = scores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let range = (max - min).max(1.0);
+    let step = rect.width() / (scores.len().max(2) - 1) as f32;
+    let point = |index: f32, score: f64| {
+        let y = rect.bottom() - 6.0 - ((score - min) / range) as f32 * (rect.height() - 12.0);
+        egui::pos2(rect.left() + index * step, y)
+    };
+    let window_start = scores.len().saturating_sub(trend.window);
+    let band = egui::Rect::from_two_pos(
+        point(window_start as f32, trend.percentiles.p75),
+        point((scores.len() - 1) as f32, trend.percentiles.p25),
+    );
+    painter.rect_filled(band, egui::Rounding::ZERO, HOVER_BG);
+    let score_line: Vec<egui::Pos2> = scores.iter().enumerate().map(|(i, s)| point(i as f32, *s)).collect();
+    painter.add(egui::Shape::line(score_line, egui::Stroke::new(1.0, TEXT_DISABLED)));
+    let average_line: Vec<egui::Pos2> = averages.iter().enumerate().map(|(i, s)| point(i as f32, *s)).collect();
+    painter.add(egui::Shape::line(average_line, egui::Stroke::new(2.0, STATUS_GREEN)));
+    let len = scores.len() as f64;
+    let trend_at = |index: usize| {
+        let position = index as f64 - (len - trend.window as f64);
+        trend.window_average + trend.slope_per_run * (position - (trend.window - 1) as f64 / 2.0)
+    };
+    let last = scores.len() - 1;
+    painter.line_segment(
+        [
+            point(window_start as f32, trend_at(window_start)),
+            point(last as f32, trend_at(last)),
+        ],
+        egui::Stroke::new(1.5, TEXT_WHITE),
+    );
+}
+fn render_sensitivity_breakdown(ui: &mut egui::Ui, runs: &[RunRecord]) {
+    section_header(ui, "Score by Sensitivity");
+    let buckets = sensitivity_breakdown(runs);
+    if buckets.is_empty() {
+        ui.label(RichText::new("No runs with a known sens scale yet").size(13.0).color(TEXT_MUTED));
+        return;
+    }
+    egui::Grid::new("sensitivity_grid")
+        .num_columns(5)
+        .spacing([20.0, 6.0])
+        .show(ui, |ui| {
+            for header in ["cm/360", "FOV", "Runs", "Average", "Best"] {
+                ui.label(RichText::new(header).size(12.0).color(TEXT_MUTED));
+            }
+            ui.end_row();
+            for (index, bucket) in buckets.iter().enumerate() {
+                let color = if index == 0 && buckets.len() > 1 { STATUS_GREEN } else { TEXT_WHITE };
+                ui.label(RichText::new(format!("{:.1}", bucket.cm_per_360)).size(13.0).color(color));
+                ui.label(RichText::new(format_optional(bucket.fov, 1)).size(13.0).color(color));
+                ui.label(RichText::new(bucket.runs.to_string()).size(13.0).color(color));
+                ui.label(RichText::new(format!("{:.1}", bucket.average_score)).size(13.0).color(color));
+                ui.label(RichText::new(format!("{:.1}", bucket.best_score)).size(13.0).color(color));
+                ui.end_row();
+            }
+        });
+}
+fn render_recent_runs(ui: &mut egui::Ui, runs: &[RunRecord]) {
+    section_header(ui, "Recent Runs");
+    egui::Grid::new("recent_runs_grid")
+        .num_columns(6)
+        .spacing([20.0, 6.0])
+        .show(ui, |ui| {
+            for header in ["Played", "Mode", "Score", "Accuracy", "Sens", "cm/360"] {
+                ui.label(RichText::new(header).size(12.0).color(TEXT_MUTED));
+            }
+            ui.end_row();
+            for run in runs.iter().rev().take(RECENT_RUNS) {
+                ui.label(RichText::new(format_timestamp(run.played_at)).size(13.0).color(TEXT_WHITE));
+                let mode = run.mode.map(|mode| mode.label()).unwrap_or("-");
+                ui.label(RichText::new(mode).size(13.0).color(TEXT_MUTED));
+                ui.label(RichText::new(format!("{:.1}", run.score)).size(13.0).color(TEXT_WHITE));
+                let accuracy = run
+                    .accuracy
+                    .map(|accuracy| format!("{:.1}%", accuracy))
+                    .unwrap_or_else(|| "-".to_string());
+                ui.label(RichText::new(accuracy).size(13.0).color(TEXT_WHITE));
+                let sens = match (&run.sens_scale, run.horizontal_sens) {
+                    (Some(scale), Some(sens)) => format!("{} {}", sens, scale),
+                    _ => "-".to_string(),
+                };
+                ui.label(RichText::new(sens).size(13.0).color(TEXT_MUTED));
+                ui.label(RichText::new(format_optional(run.cm_per_360, 1)).size(13.0).color(TEXT_WHITE));
+                ui.end_row();
+            }
+        });
+}
+fn format_optional(value: Option<f64>, decimals: usize) -> String {
+    match value {
+        Some(value) => format!("{:.*}", decimals, value),
+        None => "-".to_string(),
+    }
+}
\ No newline at end of file
@@ -1,3 +1,4 @@
+use crate::backend::{display_score, format_goal_progress, format_highscore, CountedModes, RunStats};
 use crate::state::AppState;
 use crate::ui::theme::*;
 use eframe::egui::{self, RichText};
@@ -7,13 +8,35 @@ pub struct MainTabAction {
     pub start_rpc: bool,
     pub stop_rpc: bool,
     pub minimize: bool,
+    pub set_goal: Option<(String, Option<f64>)>,
 }
-pub fn render_main_tab(ui: &mut egui::Ui, state: &Arc<AppState>) -> MainTabAction {
+/// Goal text field, reset whenever the current scenario changes.
+#[derive(Default)]
+pub struct GoalInput {
+    scenario: String,
+    text: String,
+}
+pub fn render_main_tab(ui: &mut egui::Ui, state: &Arc<AppState>, goal_input: &mut GoalInput) -> MainTabAction {
     let mut action = MainTabAction::default();
     let rpc_running = state.is_rpc_running();
     let current_scenario = state.get_current_scenario();
     let local_highscore = state.get_local_highscore();
     let session_highscore = state.get_session_highscore();
+    let session_state = state.get_session_state();
+    let last_run = state.get_last_run();
+    let current_mode = *state.current_mode.lock();
+    let (counted_modes, score_display) = {
+        let settings = state.settings.lock();
+        (settings.counted_modes, settings.show_online_scores)
+    };
+    let online_highscore = state.get_online_score_for_scenario(&current_scenario);
+    let highscore_text = format_highscore(local_highscore, online_highscore, score_display);
+    let goal_score = display_score(local_highscore, online_highscore, score_display);
+    let goal = state.get_goal_for_scenario(&current_scenario);
+    if goal_input.scenario != current_scenario {
+        goal_input.scenario = current_scenario.clone();
+        goal_input.text = goal.map(|goal| format!("{:.0}", goal)).unwrap_or_default();
+    }
 
     ui.vertical_centered(|ui| {
         ui.add_space(20.0);
@@ -65,16 +88,27 @@ pub fn render_main_tab(ui: &mut egui::Ui, state: &Arc<AppState>) -> MainTabActio
                                 ui.label(RichText::new(full_text).size(16.0).color(TEXT_WHITE));
                             }
                         );
+                        if rpc_running {
+                            ui.add_space(4.0);
+                            ui.label(RichText::new(session_state.label()).size(12.0).color(TEXT_MUTED));
+                        }
 
                         if rpc_running && !current_scenario.is_empty() && current_scenario != "Unknown Scenario" {
                             ui.add_space(20.0);
                             ui.label(RichText::new("Current Scenario").size(12.0).color(TEXT_MUTED));
                             ui.add_space(4.0);
                             ui.label(RichText::new(&current_scenario).size(18.0).color(TEXT_WHITE).strong());
+                            if let Some(mode) = current_mode {
+                                ui.label(RichText::new(mode.label()).size(12.0).color(TEXT_MUTED));
+                            }
 
                             ui.add_space(12.0);
                             ui.horizontal(|ui| {
-                                ui.label(RichText::new(format!("Highscore: {:.1}", local_highscore)).size(14.0).color(TEXT_MUTED));
+                                let highscore_label = match counted_modes {
+                                    CountedModes::Both => format!("Highscore: {}", highscore_text),
+                                    counted => format!("Highscore ({}): {}", counted.label(), highscore_text),
+                                };
+                                ui.label(RichText::new(highscore_label).size(14.0).color(TEXT_MUTED));
                                 ui.add_space(16.0);
                                 if session_highscore > 0.0 {
                                     ui.label(RichText::new(format!("Session Best: {:.1}", session_highscore)).size(14.0).color(STATUS_GREEN));
@@ -82,9 +116,41 @@ pub fn render_main_tab(ui: &mut egui::Ui, state: &Arc<AppState>) -> MainTabActio
                                     ui.label(RichText::new("No session plays yet").size(14.0).color(TEXT_MUTED));
                                 }
                             });
+                            if let Some(run) = &last_run {
+                                ui.add_space(8.0);
+                                ui.label(RichText::new(format_last_run(run)).size(12.0).color(TEXT_MUTED));
+                            }
+                            ui.add_space(12.0);
+                            if let Some(goal) = goal {
+                                let progress = (goal_score / goal).clamp(0.0, 1.0) as f32;
+                                let color = if goal_score >= goal { STATUS_GREEN } else { TEXT_WHITE };
+                                ui.label(RichText::new(format!("Goal: {}", format_goal_progress(goal_score, goal))).size(14.0).color(color));
+                                ui.add(egui::ProgressBar::new(progress).desired_width(300.0));
+                                ui.add_space(4.0);
+                            }
+                            ui.horizontal(|ui| {
+                                ui.add_space((ui.available_width() - 300.0).max(0.0) / 2.0);
+                                ui.allocate_ui_with_layout(
+                                    egui::vec2(140.0, 0.0),
+                                    egui::Layout::left_to_right(egui::Align::Center),
+                                    |ui| {
+                                        ui.set_max_width(140.0);
+                                        styled_text_edit(ui, &mut goal_input.text, "Goal score");
+                                    },
+                                );
+                                if styled_button(ui, "Set Goal", false).clicked() {
+                                    let parsed = goal_input.text.trim().parse::<f64>().ok().filter(|goal| *goal > 0.0);
+                                    action.set_goal = Some((current_scenario.clone(), parsed));
+                                }
+                            });
                         } else if rpc_running {
+                            let waiting_text = if session_state.is_game_running() {
+                                "Waiting for scenario..."
+                            } else {
+                                "Waiting for KovaaK's..."
+                            };
                             ui.add_space(20.0);
-                            ui.label(RichText::new("Waiting for scenario...").size(14.0).color(TEXT_MUTED));
+                            ui.label(RichText::new(waiting_text).size(14.0).color(TEXT_MUTED));
                         }
                     });
                 });
@@ -108,7 +174,8 @@ pub fn render_main_tab(ui: &mut egui::Ui, state: &Arc<AppState>) -> MainTabActio
                     }
                 }
                 ui.add_space(16.0);
-                if styled_button(ui, "Minimize to Tray", false).clicked() {
+                let minimize_label = if cfg!(feature = "tray") { "Minimize to Tray" } else { "Minimize" };
+                if styled_button(ui, minimize_label, false).clicked() {
                     action.minimize = true;
                 }
             }
@@ -123,4 +190,18 @@ pub fn render_main_tab(ui: &mut egui::Ui, state: &Arc<AppState>) -> MainTabActio
         );
     });
     action
+}
+fn format_last_run(run: &RunStats) -> String {
+    let mut parts = vec![format!("Last run: {:.1}", run.score)];
+    if let Some(accuracy) = run.accuracy() {
+        parts.push(format!("{:.1}% accuracy", accuracy));
+    }
+    parts.push(format!("{} kills", run.kills));
+    if let Some(avg_ttk) = run.avg_ttk {
+        parts.push(format!("{:.3}s avg TTK", avg_ttk));
+    }
+    if let Some(efficiency) = run.damage_efficiency() {
+        parts.push(format!("{:.1}% efficiency", efficiency));
+    }
+    parts.join("  ·  ")
 }
\ No newline at end of file
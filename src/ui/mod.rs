@@ -1,6 +1,18 @@
+pub mod calendar_tab;
+pub mod diagnostics_tab;
+pub mod history_tab;
 pub mod main_tab;
+pub mod scores_tab;
+pub mod sessions_tab;
 pub mod settings_tab;
+pub mod setup_wizard;
 pub mod theme;
-pub use main_tab::render_main_tab;
-pub use settings_tab::{render_settings_tab, SettingsForm};
+pub use calendar_tab::render_calendar_tab;
+pub use diagnostics_tab::render_diagnostics_tab;
+pub use history_tab::{render_history_tab, HistoryView};
+pub use main_tab::{render_main_tab, GoalInput};
+pub use scores_tab::{render_scores_tab, ScoresView};
+pub use sessions_tab::{render_sessions_tab, ExportFormat, SessionsView};
+pub use settings_tab::{render_settings_tab, ProfilesForm, SettingsForm};
+pub use setup_wizard::{render_setup_wizard, SetupWizard};
 pub use theme::*;
\ No newline at end of file
@@ -0,0 +1,84 @@
+use crate::backend::{format_timestamp, ScenarioScore};
+use crate::ui::theme::*;
+use eframe::egui::{self, RichText};
+use std::collections::HashMap;
+/// Whether every scenario with an online score is listed or only the ones that disagree.
+pub struct ScoresView {
+    only_disagreements: bool,
+}
+impl Default for ScoresView {
+    fn default() -> Self {
+        Self {
+            only_disagreements: true,
+        }
+    }
+}
+pub fn render_scores_tab(ui: &mut egui::Ui, view: &mut ScoresView, scores: &HashMap<String, ScenarioScore>) {
+    let mut rows: Vec<&ScenarioScore> = scores
+        .values()
+        .filter(|score| score.online_highscore.is_some())
+        .filter(|score| !view.only_disagreements || score.disagrees())
+        .collect();
+    rows.sort_by(|a, b| difference(b).abs().total_cmp(&difference(a).abs()));
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        let max_width = 600.0;
+        ui.vertical_centered(|ui| {
+            ui.add_space(16.0);
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        section_header(ui, "Local vs. Online");
+                        styled_checkbox(ui, &mut view.only_disagreements, "Only scenarios where they differ");
+                        ui.add_space(8.0);
+                        if rows.is_empty() {
+                            let text = if scores.values().any(|score| score.online_highscore.is_some()) {
+                                "Your local and online highscores agree"
+                            } else {
+                                "Sync your online scores in Settings to compare them"
+                            };
+                            ui.label(RichText::new(text).size(13.0).color(TEXT_MUTED));
+                            return;
+                        }
+                        egui::Grid::new("scores_grid")
+                            .num_columns(5)
+                            .spacing([20.0, 6.0])
+                            .show(ui, |ui| {
+                                for header in ["Scenario", "Local", "Online", "Difference", "Local best set"] {
+                                    ui.label(RichText::new(header).size(12.0).color(TEXT_MUTED));
+                                }
+                                ui.end_row();
+                                for score in &rows {
+                                    let online = score.online_highscore.unwrap_or(0.0);
+                                    let diff = difference(score);
+                                    let color = if diff > 0.0 { STATUS_GREEN } else if diff < 0.0 { STATUS_RED } else { TEXT_WHITE };
+                                    ui.label(RichText::new(&score.scenario_name).size(13.0).color(TEXT_WHITE));
+                                    ui.label(RichText::new(format!("{:.1}", score.highscore)).size(13.0).color(TEXT_WHITE));
+                                    ui.label(RichText::new(format!("{:.1}", online)).size(13.0).color(TEXT_WHITE));
+                                    ui.label(RichText::new(format!("{:+.1}", diff)).size(13.0).color(color));
+                                    let set_at = score
+                                        .highscore_at
+                                        .map(format_timestamp)
+                                        .unwrap_or_else(|| "-".to_string());
+                                    ui.label(RichText::new(set_at).size(13.0).color(TEXT_MUTED));
+                                    ui.end_row();
+                                }
+                            });
+                        if let Some(synced_at) = rows.iter().filter_map(|score| score.online_synced_at).max() {
+                            ui.add_space(8.0);
+                            ui.label(RichText::new(format!("Online scores synced {}", format_timestamp(synced_at))).size(11.0).color(TEXT_DISABLED));
+                        }
+                    });
+                },
+            );
+            ui.add_space(24.0);
+        });
+    });
+}
+/// Local minus online highscore.
+fn difference(score: &ScenarioScore) -> f64 {
+    score.highscore - score.online_highscore.unwrap_or(0.0)
+}
\ No newline at end of file
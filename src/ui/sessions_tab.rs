@@ -0,0 +1,141 @@
+use crate::backend::{format_duration, format_timestamp, SessionLogManager, SessionSummary};
+use crate::ui::theme::*;
+use eframe::egui::{self, RichText};
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
+/// Logged sessions and the one being looked at, reloaded from disk only when stale.
+#[derive(Default)]
+pub struct SessionsView {
+    sessions: Vec<SessionSummary>,
+    selected: usize,
+    loaded: bool,
+}
+impl SessionsView {
+    pub fn mark_stale(&mut self) {
+        self.loaded = false;
+    }
+    fn refresh(&mut self, log: &SessionLogManager) {
+        self.sessions = log.sessions().unwrap_or_default();
+        self.selected = 0;
+        self.loaded = true;
+    }
+}
+#[derive(Default)]
+pub struct SessionsTabAction {
+    pub export: Option<(SessionSummary, ExportFormat)>,
+}
+pub fn render_sessions_tab(ui: &mut egui::Ui, view: &mut SessionsView, log: &SessionLogManager) -> SessionsTabAction {
+    let mut action = SessionsTabAction::default();
+    if !view.loaded {
+        view.refresh(log);
+    }
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        let max_width = 600.0;
+        ui.vertical_centered(|ui| {
+            ui.add_space(16.0);
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        section_header(ui, "Sessions");
+                        if view.sessions.is_empty() {
+                            ui.label(RichText::new("A summary shows up here after you close KovaaK's").size(13.0).color(TEXT_MUTED));
+                            return;
+                        }
+                        egui::ComboBox::from_id_salt("session_select")
+                            .width(max_width - 40.0)
+                            .selected_text(session_label(&view.sessions[view.selected]))
+                            .show_ui(ui, |ui| {
+                                for (index, session) in view.sessions.iter().enumerate() {
+                                    ui.selectable_value(&mut view.selected, index, session_label(session));
+                                }
+                            });
+                    });
+                },
+            );
+            ui.add_space(20.0);
+
+            let Some(session) = view.sessions.get(view.selected) else {
+                return;
+            };
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        render_session_details(ui, session);
+                        ui.add_space(12.0);
+                        ui.horizontal(|ui| {
+                            if styled_button(ui, "Export Markdown", false).clicked() {
+                                action.export = Some((session.clone(), ExportFormat::Markdown));
+                            }
+                            if styled_button(ui, "Export JSON", false).clicked() {
+                                action.export = Some((session.clone(), ExportFormat::Json));
+                            }
+                        });
+                    });
+                },
+            );
+            ui.add_space(24.0);
+        });
+    });
+    action
+}
+fn session_label(session: &SessionSummary) -> String {
+    format!(
+        "{}  ·  {}  ·  {} runs",
+        format_timestamp(session.started_at),
+        format_duration(session.active_secs),
+        session.total_runs()
+    )
+}
+fn render_session_details(ui: &mut egui::Ui, session: &SessionSummary) {
+    section_header(ui, "Summary");
+    ui.label(RichText::new(format!(
+        "{} played, {} scenarios, {} runs, {} new PBs",
+        format_duration(session.active_secs),
+        session.scenarios.len(),
+        session.total_runs(),
+        session.personal_bests().count()
+    )).size(13.0).color(TEXT_WHITE));
+    let improvements = session.best_improvements();
+    if let Some((scenario, gain)) = improvements.first() {
+        ui.label(RichText::new(format!("Biggest improvement: {} ({:+.1}% vs. previous average)", scenario.scenario, gain)).size(12.0).color(STATUS_GREEN));
+    }
+    ui.add_space(8.0);
+    egui::Grid::new("session_grid")
+        .num_columns(5)
+        .spacing([20.0, 6.0])
+        .show(ui, |ui| {
+            for header in ["Scenario", "Runs", "Best", "Average", "Change"] {
+                ui.label(RichText::new(header).size(12.0).color(TEXT_MUTED));
+            }
+            ui.end_row();
+            for scenario in &session.scenarios {
+                let color = if scenario.is_pb { STATUS_GREEN } else { TEXT_WHITE };
+                let name = if scenario.is_pb {
+                    format!("{} (PB)", scenario.scenario)
+                } else {
+                    scenario.scenario.clone()
+                };
+                ui.label(RichText::new(name).size(13.0).color(color));
+                ui.label(RichText::new(scenario.runs.to_string()).size(13.0).color(TEXT_WHITE));
+                ui.label(RichText::new(format!("{:.1}", scenario.best_score)).size(13.0).color(color));
+                ui.label(RichText::new(format!("{:.1}", scenario.average_score)).size(13.0).color(TEXT_WHITE));
+                let change = scenario
+                    .improvement()
+                    .map(|gain| format!("{:+.1}%", gain))
+                    .unwrap_or_else(|| "-".to_string());
+                ui.label(RichText::new(change).size(13.0).color(TEXT_MUTED));
+                ui.end_row();
+            }
+        });
+}
\ No newline at end of file
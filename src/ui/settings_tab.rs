@@ -1,4 +1,8 @@
-use crate::backend::{get_autostart_enabled, set_autostart_enabled, Settings};
+use crate::backend::{
+    format_alias_list, get_autostart_enabled, parse_alias_list, parse_pattern_list,
+    set_autostart_enabled, CountedModes, ProfilesFile, ScoreDisplay, Settings, ValidationEntry,
+    PRESENCE_PLACEHOLDERS,
+};
 use crate::ui::theme::*;
 use eframe::egui::{self, RichText};
 pub struct SettingsForm {
@@ -7,10 +11,26 @@ pub struct SettingsForm {
     pub open_manually: bool,
     pub start_with_windows: bool,
     pub webapp_username: String,
-    pub show_online_scores: bool,
+    pub show_online_scores: ScoreDisplay,
     pub start_in_tray: bool,
     pub online_only_scenarios: bool,
     pub online_scores_synced: bool,
+    pub idle_timeout_minutes: u64,
+    pub clear_presence_when_idle: bool,
+    pub scenario_allowlist: String,
+    pub scenario_blocklist: String,
+    pub hide_scores: bool,
+    pub streamer_mode: bool,
+    pub presence_details_template: String,
+    pub presence_state_template: String,
+    pub mouse_dpi: u32,
+    pub webhook_url: String,
+    pub webhook_post_pbs: bool,
+    pub webhook_post_sessions: bool,
+    pub obs_output_enabled: bool,
+    pub obs_output_dir: String,
+    pub counted_modes: CountedModes,
+    pub scenario_aliases: String,
 }
 impl From<&Settings> for SettingsForm {
     fn from(settings: &Settings) -> Self {
@@ -24,6 +44,22 @@ impl From<&Settings> for SettingsForm {
             start_in_tray: settings.start_in_tray,
             online_only_scenarios: settings.online_only_scenarios,
             online_scores_synced: settings.online_scores_synced,
+            idle_timeout_minutes: settings.idle_timeout_minutes,
+            clear_presence_when_idle: settings.clear_presence_when_idle,
+            scenario_allowlist: settings.scenario_allowlist.join("\n"),
+            scenario_blocklist: settings.scenario_blocklist.join("\n"),
+            hide_scores: settings.hide_scores,
+            streamer_mode: settings.streamer_mode,
+            presence_details_template: settings.presence_details_template.clone(),
+            presence_state_template: settings.presence_state_template.clone(),
+            mouse_dpi: settings.mouse_dpi,
+            webhook_url: settings.webhook_url.clone(),
+            webhook_post_pbs: settings.webhook_post_pbs,
+            webhook_post_sessions: settings.webhook_post_sessions,
+            obs_output_enabled: settings.obs_output_enabled,
+            obs_output_dir: settings.obs_output_dir.clone(),
+            counted_modes: settings.counted_modes,
+            scenario_aliases: format_alias_list(&settings.scenario_aliases),
         }
     }
 }
@@ -40,20 +76,67 @@ impl SettingsForm {
             online_only_scenarios: self.online_only_scenarios,
             online_scores_synced: self.online_scores_synced,
             last_sync_time,
+            idle_timeout_minutes: self.idle_timeout_minutes,
+            clear_presence_when_idle: self.clear_presence_when_idle,
+            scenario_allowlist: parse_pattern_list(&self.scenario_allowlist),
+            scenario_blocklist: parse_pattern_list(&self.scenario_blocklist),
+            hide_scores: self.hide_scores,
+            streamer_mode: self.streamer_mode,
+            presence_details_template: self.presence_details_template.clone(),
+            presence_state_template: self.presence_state_template.clone(),
+            mouse_dpi: self.mouse_dpi,
+            webhook_url: self.webhook_url.trim().to_string(),
+            webhook_post_pbs: self.webhook_post_pbs,
+            webhook_post_sessions: self.webhook_post_sessions,
+            obs_output_enabled: self.obs_output_enabled,
+            obs_output_dir: self.obs_output_dir.trim().to_string(),
+            counted_modes: self.counted_modes,
+            scenario_aliases: parse_alias_list(&self.scenario_aliases),
+            setup_complete: true,
+        }
+    }
+}
+/// Profile list and the profile picker's inputs.
+#[derive(Default)]
+pub struct ProfilesForm {
+    pub names: Vec<String>,
+    pub active: String,
+    pub selected: String,
+    pub new_name: String,
+}
+impl From<&ProfilesFile> for ProfilesForm {
+    fn from(profiles: &ProfilesFile) -> Self {
+        let active = profiles.active_name().to_string();
+        Self {
+            names: profiles.names(),
+            selected: active.clone(),
+            active,
+            new_name: String::new(),
         }
     }
 }
 #[derive(Default)]
 pub struct SettingsTabAction {
     pub scan_stats: bool,
+    pub cancel_scan: bool,
     pub sync_online: bool,
     pub reset_sync: bool,
     pub save: bool,
+    pub validation_override: Option<(String, Option<bool>)>,
+    pub validation_refresh: Option<String>,
+    pub purge_validation_cache: bool,
+    pub test_webhook: bool,
+    pub import_goals: bool,
+    pub switch_profile: Option<String>,
+    pub create_profile: Option<String>,
 }
 pub fn render_settings_tab(
     ui: &mut egui::Ui,
     form: &mut SettingsForm,
+    profiles: &mut ProfilesForm,
     is_syncing: bool,
+    is_scanning: bool,
+    validation_entries: &[(String, ValidationEntry)],
 ) -> SettingsTabAction {
     let mut action = SettingsTabAction::default();
     egui::ScrollArea::vertical().show(ui, |ui| {
@@ -61,6 +144,18 @@ pub fn render_settings_tab(
         ui.vertical_centered(|ui| {
             ui.add_space(16.0);
 
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        render_profiles(ui, profiles, max_width, &mut action);
+                    });
+                },
+            );
+            ui.add_space(20.0);
+
             ui.allocate_ui_with_layout(
                 egui::vec2(max_width, 0.0),
                 egui::Layout::top_down(egui::Align::Center),
@@ -79,6 +174,14 @@ pub fn render_settings_tab(
                         }
                         ui.add_space(12.0);
                         styled_checkbox(ui, &mut form.start_in_tray, "Start minimized to system tray");
+                        ui.add_space(12.0);
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("Idle after").size(13.0).color(TEXT_WHITE));
+                            ui.add(egui::DragValue::new(&mut form.idle_timeout_minutes).range(0..=240).suffix(" min"));
+                        });
+                        ui.label(RichText::new("Switches presence to idle when no runs are played and the game is untouched (0 disables)").size(11.0).color(TEXT_DISABLED));
+                        ui.add_space(8.0);
+                        styled_checkbox(ui, &mut form.clear_presence_when_idle, "Clear presence while idle");
                     });
                 },
             );
@@ -90,60 +193,185 @@ pub fn render_settings_tab(
                 |ui| {
                     card_frame().show(ui, |ui| {
                         ui.set_max_width(max_width);
-                        section_header(ui, "Online Features");
-                        ui.label(RichText::new("Kovaak Webapp Username").size(13.0).color(TEXT_WHITE));
+                        section_header(ui, "Privacy");
+                        styled_checkbox(ui, &mut form.hide_scores, "Hide scores (only show the scenario name)");
+                        ui.add_space(8.0);
+                        styled_checkbox(ui, &mut form.streamer_mode, "Streamer mode (hide the scenario name)");
+                        ui.add_space(12.0);
+                        ui.label(RichText::new("Hidden Scenarios").size(13.0).color(TEXT_WHITE));
+                        ui.label(RichText::new("One pattern per line, * and ? wildcards, e.g. *private*").size(11.0).color(TEXT_DISABLED));
                         ui.add_space(4.0);
-                        styled_text_edit(ui, &mut form.webapp_username, "Enter your username");
+                        styled_multiline_edit(ui, &mut form.scenario_blocklist, "*private*");
                         ui.add_space(12.0);
-                        let has_username = !form.webapp_username.is_empty();
-                        ui.add_enabled_ui(has_username, |ui| {
-                            styled_checkbox(ui, &mut form.show_online_scores, "Show online scenario highscores");
-                        });
+                        ui.label(RichText::new("Only Show Scenarios").size(13.0).color(TEXT_WHITE));
+                        ui.label(RichText::new("Leave empty to show every scenario that isn't hidden").size(11.0).color(TEXT_DISABLED));
+                        ui.add_space(4.0);
+                        styled_multiline_edit(ui, &mut form.scenario_allowlist, "Gridshot*");
+                    });
+                },
+            );
+            ui.add_space(20.0);
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        section_header(ui, "Presence Text");
+                        ui.label(RichText::new("Details").size(13.0).color(TEXT_WHITE));
+                        ui.add_space(4.0);
+                        styled_text_edit(ui, &mut form.presence_details_template, "Playing: {scenario}");
+                        ui.add_space(12.0);
+                        ui.label(RichText::new("State").size(13.0).color(TEXT_WHITE));
+                        ui.add_space(4.0);
+                        styled_text_edit(ui, &mut form.presence_state_template, "Highscore: {highscore}");
                         ui.add_space(8.0);
-                        styled_checkbox(ui, &mut form.online_only_scenarios, "Only show scenarios available online");
-                        ui.add_space(16.0);
-                        ui.separator();
-                        ui.add_space(16.0);
+                        let placeholders: Vec<String> = PRESENCE_PLACEHOLDERS
+                            .iter()
+                            .map(|name| format!("{{{}}}", name))
+                            .collect();
+                        ui.label(RichText::new(format!("Placeholders: {}", placeholders.join(", "))).size(11.0).color(TEXT_DISABLED));
+                    });
+                },
+            );
+            ui.add_space(20.0);
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        section_header(ui, "OBS Output");
+                        styled_checkbox(ui, &mut form.obs_output_enabled, "Write text files for OBS");
+                        ui.label(RichText::new("scenario.txt, highscore.txt, session_best.txt, playlist.txt, playlist_progress.txt, status.txt and kovaaks.json").size(11.0).color(TEXT_DISABLED));
+                        ui.add_space(8.0);
+                        ui.add_enabled_ui(form.obs_output_enabled, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.set_max_width(max_width);
+
+                                let text_edit_width = max_width - 120.0;
+                                ui.allocate_ui_with_layout(
+                                    egui::vec2(text_edit_width, 0.0),
+                                    egui::Layout::left_to_right(egui::Align::Center),
+                                    |ui| {
+                                        ui.set_max_width(text_edit_width);
+                                        styled_text_edit(ui, &mut form.obs_output_dir, "Empty uses the obs folder in app data");
+                                    }
+                                );
+
+                                if styled_button(ui, "Browse", false).clicked() {
+                                    if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                        form.obs_output_dir = path.display().to_string();
+                                    }
+                                }
+                            });
+                        });
+                    });
+                },
+            );
+            ui.add_space(20.0);
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        #[cfg(feature = "online")]
+                        render_online_account(ui, form);
+                        #[cfg(not(feature = "online"))]
+                        section_header(ui, "Local Stats");
 
                         ui.horizontal(|ui| {
                             ui.vertical(|ui| {
                                 ui.label(RichText::new("Scan Local Stats").size(14.0).color(TEXT_WHITE));
-                                ui.label(RichText::new("Import scores from your stats folder").size(11.0).color(TEXT_MUTED));
+                                ui.label(RichText::new("Import scores from your stats folder, only new files are read again").size(11.0).color(TEXT_MUTED));
                             });
                             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                if styled_button(ui, "Scan Stats", false).clicked() {
+                                if is_scanning {
+                                    if styled_button(ui, "Cancel Scan", false).clicked() {
+                                        action.cancel_scan = true;
+                                    }
+                                } else if styled_button(ui, "Scan Stats", false).clicked() {
                                     action.scan_stats = true;
                                 }
                             });
                         });
                         ui.add_space(12.0);
-
                         ui.horizontal(|ui| {
                             ui.vertical(|ui| {
-                                ui.label(RichText::new("Sync Online Scores").size(14.0).color(TEXT_WHITE));
-                                ui.label(RichText::new("Fetch highscores from Kovaak's webapp").size(11.0).color(TEXT_MUTED));
+                                ui.label(RichText::new("Import Goals").size(14.0).color(TEXT_WHITE));
+                                ui.label(RichText::new("CSV with one \"scenario,goal\" row per scenario").size(11.0).color(TEXT_MUTED));
                             });
                             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                if form.online_scores_synced {
-                                    if styled_button(ui, "Re-sync", false).clicked() {
-                                        action.reset_sync = true;
-                                    }
-                                } else {
-                                    let sync_enabled = has_username && !is_syncing;
-                                    ui.add_enabled_ui(sync_enabled, |ui| {
-                                        let text = if is_syncing { "Syncing..." } else { "Sync Now" };
-                                        if styled_button(ui, text, true).clicked() {
-                                            action.sync_online = true;
-                                        }
-                                    });
+                                if styled_button(ui, "Import CSV", false).clicked() {
+                                    action.import_goals = true;
                                 }
                             });
                         });
+                        #[cfg(feature = "online")]
+                        render_sync_row(ui, form, is_syncing, &mut action);
+                        #[cfg(not(feature = "online"))]
+                        let _ = is_syncing;
+                        ui.add_space(12.0);
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("Mouse DPI").size(13.0).color(TEXT_WHITE));
+                            ui.add(egui::DragValue::new(&mut form.mouse_dpi).range(100..=32000).speed(50));
+                        });
+                        ui.label(RichText::new("Used to turn the sensitivity of each run into cm/360").size(11.0).color(TEXT_DISABLED));
+                        ui.add_space(12.0);
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("Highscore counts").size(13.0).color(TEXT_WHITE));
+                            egui::ComboBox::from_id_salt("counted_modes")
+                                .selected_text(form.counted_modes.label())
+                                .show_ui(ui, |ui| {
+                                    for counted in CountedModes::ALL {
+                                        ui.selectable_value(&mut form.counted_modes, counted, counted.label());
+                                    }
+                                });
+                        });
+                        ui.label(RichText::new("Which runs count toward the highscore shown on Discord and in the app").size(11.0).color(TEXT_DISABLED));
+                        ui.add_space(12.0);
+                        ui.label(RichText::new("Renamed Scenarios").size(13.0).color(TEXT_WHITE));
+                        ui.label(RichText::new("One \"Old Name = New Name\" per line, scores and runs of the old name are kept under the new one").size(11.0).color(TEXT_DISABLED));
+                        ui.add_space(4.0);
+                        styled_multiline_edit(ui, &mut form.scenario_aliases, "Old Name = New Name");
                     });
                 },
             );
             ui.add_space(20.0);
 
+            #[cfg(feature = "online")]
+            {
+                ui.allocate_ui_with_layout(
+                    egui::vec2(max_width, 0.0),
+                    egui::Layout::top_down(egui::Align::Center),
+                    |ui| {
+                        card_frame().show(ui, |ui| {
+                            ui.set_max_width(max_width);
+                            render_webhook_settings(ui, form, &mut action);
+                        });
+                    },
+                );
+                ui.add_space(20.0);
+            }
+
+            if form.online_only_scenarios {
+                ui.allocate_ui_with_layout(
+                    egui::vec2(max_width, 0.0),
+                    egui::Layout::top_down(egui::Align::Center),
+                    |ui| {
+                        card_frame().show(ui, |ui| {
+                            ui.set_max_width(max_width);
+                            render_validation_cache(ui, validation_entries, &mut action);
+                        });
+                    },
+                );
+                ui.add_space(20.0);
+            }
+
             ui.allocate_ui_with_layout(
                 egui::vec2(max_width, 0.0),
                 egui::Layout::top_down(egui::Align::Center),
@@ -209,4 +437,173 @@ pub fn render_settings_tab(
         });
     });
     action
+}
+fn render_profiles(ui: &mut egui::Ui, profiles: &mut ProfilesForm, max_width: f32, action: &mut SettingsTabAction) {
+    section_header(ui, "Profile");
+    ui.label(RichText::new("Each profile has its own paths, webapp account, scores and history").size(11.0).color(TEXT_DISABLED));
+    ui.add_space(8.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Active profile").size(13.0).color(TEXT_WHITE));
+        egui::ComboBox::from_id_salt("profile")
+            .selected_text(profiles.selected.as_str())
+            .show_ui(ui, |ui| {
+                for name in &profiles.names {
+                    ui.selectable_value(&mut profiles.selected, name.clone(), name.as_str());
+                }
+            });
+        ui.add_enabled_ui(profiles.selected != profiles.active, |ui| {
+            if styled_button(ui, "Switch", false).clicked() {
+                action.switch_profile = Some(profiles.selected.clone());
+            }
+        });
+    });
+    ui.label(RichText::new("Switching restarts the app, unsaved settings are discarded").size(11.0).color(TEXT_DISABLED));
+    ui.add_space(12.0);
+    ui.label(RichText::new("New Profile").size(13.0).color(TEXT_WHITE));
+    ui.add_space(4.0);
+    ui.horizontal(|ui| {
+        ui.set_max_width(max_width);
+        let text_edit_width = max_width - 120.0;
+        ui.allocate_ui_with_layout(
+            egui::vec2(text_edit_width, 0.0),
+            egui::Layout::left_to_right(egui::Align::Center),
+            |ui| {
+                ui.set_max_width(text_edit_width);
+                styled_text_edit(ui, &mut profiles.new_name, "Profile name");
+            }
+        );
+        ui.add_enabled_ui(!profiles.new_name.trim().is_empty(), |ui| {
+            if styled_button(ui, "Create", false).clicked() {
+                action.create_profile = Some(profiles.new_name.trim().to_string());
+            }
+        });
+    });
+    ui.label(RichText::new("Starts from these settings without the webapp account, setup runs when you switch to it").size(11.0).color(TEXT_DISABLED));
+}
+#[cfg(feature = "online")]
+fn render_online_account(ui: &mut egui::Ui, form: &mut SettingsForm) {
+    section_header(ui, "Online Features");
+    ui.label(RichText::new("Kovaak Webapp Username").size(13.0).color(TEXT_WHITE));
+    ui.add_space(4.0);
+    styled_text_edit(ui, &mut form.webapp_username, "Enter your username");
+    ui.add_space(12.0);
+    let has_username = !form.webapp_username.is_empty();
+    ui.add_enabled_ui(has_username, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Highscore shown").size(13.0).color(TEXT_WHITE));
+            egui::ComboBox::from_id_salt("score_display")
+                .selected_text(form.show_online_scores.label())
+                .show_ui(ui, |ui| {
+                    for display in ScoreDisplay::ALL {
+                        ui.selectable_value(&mut form.show_online_scores, display, display.label());
+                    }
+                });
+        });
+    });
+    ui.add_space(8.0);
+    styled_checkbox(ui, &mut form.online_only_scenarios, "Only show scenarios available online");
+    ui.add_space(16.0);
+    ui.separator();
+    ui.add_space(16.0);
+}
+#[cfg(feature = "online")]
+fn render_sync_row(ui: &mut egui::Ui, form: &SettingsForm, is_syncing: bool, action: &mut SettingsTabAction) {
+    let has_username = !form.webapp_username.is_empty();
+    ui.add_space(12.0);
+    ui.horizontal(|ui| {
+        ui.vertical(|ui| {
+            ui.label(RichText::new("Sync Online Scores").size(14.0).color(TEXT_WHITE));
+            ui.label(RichText::new("Fetch highscores from Kovaak's webapp").size(11.0).color(TEXT_MUTED));
+        });
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if form.online_scores_synced {
+                if styled_button(ui, "Re-sync", false).clicked() {
+                    action.reset_sync = true;
+                }
+            } else {
+                let sync_enabled = has_username && !is_syncing;
+                ui.add_enabled_ui(sync_enabled, |ui| {
+                    let text = if is_syncing { "Syncing..." } else { "Sync Now" };
+                    if styled_button(ui, text, true).clicked() {
+                        action.sync_online = true;
+                    }
+                });
+            }
+        });
+    });
+}
+#[cfg(feature = "online")]
+fn render_webhook_settings(ui: &mut egui::Ui, form: &mut SettingsForm, action: &mut SettingsTabAction) {
+    section_header(ui, "Discord Webhook");
+    ui.label(RichText::new("Posts new PBs and session summaries to a channel, leave empty to turn off").size(11.0).color(TEXT_DISABLED));
+    ui.add_space(4.0);
+    styled_text_edit(ui, &mut form.webhook_url, "https://discord.com/api/webhooks/...");
+    ui.add_space(12.0);
+    let has_url = !form.webhook_url.trim().is_empty();
+    ui.add_enabled_ui(has_url, |ui| {
+        styled_checkbox(ui, &mut form.webhook_post_pbs, "Post new personal bests");
+        ui.add_space(8.0);
+        styled_checkbox(ui, &mut form.webhook_post_sessions, "Post session summaries");
+        ui.add_space(12.0);
+        if styled_button(ui, "Send Test", false).clicked() {
+            action.test_webhook = true;
+        }
+    });
+}
+fn render_validation_cache(
+    ui: &mut egui::Ui,
+    entries: &[(String, ValidationEntry)],
+    action: &mut SettingsTabAction,
+) {
+    section_header(ui, "Online Scenario Checks");
+    ui.label(RichText::new("Cached results of the online scenario lookup, force a scenario to always show or hide").size(11.0).color(TEXT_DISABLED));
+    ui.add_space(8.0);
+    if entries.is_empty() {
+        ui.label(RichText::new("No scenarios checked yet").size(13.0).color(TEXT_MUTED));
+        return;
+    }
+    egui::ScrollArea::vertical()
+        .id_salt("validation_cache")
+        .max_height(220.0)
+        .show(ui, |ui| {
+            egui::Grid::new("validation_cache_grid")
+                .num_columns(4)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    for (name, entry) in entries {
+                        ui.label(RichText::new(name).size(13.0).color(TEXT_WHITE));
+                        let verdict = if entry.is_expired() {
+                            "Expired"
+                        } else if entry.is_valid {
+                            "Online"
+                        } else {
+                            "Not found"
+                        };
+                        ui.label(RichText::new(verdict).size(12.0).color(TEXT_MUTED));
+                        let mut selected = entry.manual_override;
+                        egui::ComboBox::from_id_salt(("validation_override", name))
+                            .selected_text(match selected {
+                                None => "Auto",
+                                Some(true) => "Always show",
+                                Some(false) => "Always hide",
+                            })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut selected, None, "Auto");
+                                ui.selectable_value(&mut selected, Some(true), "Always show");
+                                ui.selectable_value(&mut selected, Some(false), "Always hide");
+                            });
+                        if selected != entry.manual_override {
+                            action.validation_override = Some((name.clone(), selected));
+                        }
+                        if ui.small_button("Recheck").clicked() {
+                            action.validation_refresh = Some(name.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    ui.add_space(8.0);
+    if styled_button(ui, "Clear Expired", false).clicked() {
+        action.purge_validation_cache = true;
+    }
 }
\ No newline at end of file
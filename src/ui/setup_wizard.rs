@@ -0,0 +1,171 @@
+use crate::backend::{count_stats_files, find_installations, InstallCandidate};
+use crate::ui::theme::*;
+use eframe::egui::{self, RichText};
+use std::path::Path;
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WizardStep {
+    Installation,
+    Account,
+}
+/// First-run setup: pick the installation, then optionally an account and the first import.
+pub struct SetupWizard {
+    step: WizardStep,
+    candidates: Vec<InstallCandidate>,
+    pub installation_path: String,
+    /// Path the stats count below belongs to, recounted when the path changes.
+    counted_path: String,
+    stats_files: Option<usize>,
+    pub webapp_username: String,
+    pub import_stats: bool,
+    pub sync_online: bool,
+}
+impl SetupWizard {
+    /// Looks for installations, preselecting the one with the most runs or the already set path.
+    pub fn new(installation_path: &str, webapp_username: &str) -> Self {
+        let candidates = find_installations();
+        let installation_path = if installation_path.trim().is_empty() {
+            candidates
+                .first()
+                .map(|candidate| candidate.path.display().to_string())
+                .unwrap_or_default()
+        } else {
+            installation_path.to_string()
+        };
+        Self {
+            step: WizardStep::Installation,
+            candidates,
+            installation_path,
+            counted_path: String::new(),
+            stats_files: None,
+            webapp_username: webapp_username.to_string(),
+            import_stats: true,
+            sync_online: true,
+        }
+    }
+    fn refresh_stats_count(&mut self) {
+        if self.counted_path == self.installation_path {
+            return;
+        }
+        self.counted_path = self.installation_path.clone();
+        let path = Path::new(self.installation_path.trim());
+        self.stats_files = if path.join("stats").is_dir() {
+            Some(count_stats_files(path))
+        } else {
+            None
+        };
+    }
+}
+#[derive(Default)]
+pub struct SetupWizardAction {
+    pub finish: bool,
+    pub skip: bool,
+}
+pub fn render_setup_wizard(ui: &mut egui::Ui, wizard: &mut SetupWizard) -> SetupWizardAction {
+    let mut action = SetupWizardAction::default();
+    wizard.refresh_stats_count();
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        let max_width = 600.0;
+        ui.vertical_centered(|ui| {
+            ui.add_space(16.0);
+
+            ui.allocate_ui_with_layout(
+                egui::vec2(max_width, 0.0),
+                egui::Layout::top_down(egui::Align::Center),
+                |ui| {
+                    card_frame().show(ui, |ui| {
+                        ui.set_max_width(max_width);
+                        match wizard.step {
+                            WizardStep::Installation => render_installation_step(ui, wizard, max_width),
+                            WizardStep::Account => render_account_step(ui, wizard),
+                        }
+                    });
+                },
+            );
+            ui.add_space(24.0);
+
+            ui.horizontal(|ui| {
+                ui.add_space((ui.available_width() - 330.0).max(0.0) / 2.0);
+                if styled_button(ui, "Skip Setup", false).clicked() {
+                    action.skip = true;
+                }
+                match wizard.step {
+                    WizardStep::Installation => {
+                        ui.add_enabled_ui(wizard.stats_files.is_some(), |ui| {
+                            if styled_button(ui, "Next", true).clicked() {
+                                wizard.step = WizardStep::Account;
+                            }
+                        });
+                    }
+                    WizardStep::Account => {
+                        if styled_button(ui, "Back", false).clicked() {
+                            wizard.step = WizardStep::Installation;
+                        }
+                        if styled_button(ui, "Finish", true).clicked() {
+                            action.finish = true;
+                        }
+                    }
+                }
+            });
+        });
+    });
+    action
+}
+fn render_installation_step(ui: &mut egui::Ui, wizard: &mut SetupWizard, max_width: f32) {
+    section_header(ui, "Welcome! Where is KovaaK's installed?");
+    if wizard.candidates.is_empty() {
+        ui.label(RichText::new("No installation was found automatically, pick the FPSAimTrainer folder below").size(12.0).color(TEXT_MUTED));
+    }
+    for candidate in &wizard.candidates {
+        let path = candidate.path.display().to_string();
+        let selected = wizard.installation_path == path;
+        let text = RichText::new(format!("{}\n{} · {} runs", path, candidate.source, candidate.stats_files))
+            .size(13.0)
+            .color(if selected { TEXT_WHITE } else { TEXT_MUTED });
+        if ui.selectable_label(selected, text).clicked() {
+            wizard.installation_path = path;
+        }
+        ui.add_space(4.0);
+    }
+    ui.add_space(8.0);
+    ui.label(RichText::new("FPSAimTrainer Installation Path").size(13.0).color(TEXT_WHITE));
+    ui.add_space(4.0);
+    ui.horizontal(|ui| {
+        ui.set_max_width(max_width);
+        let text_edit_width = max_width - 120.0;
+        ui.allocate_ui_with_layout(
+            egui::vec2(text_edit_width, 0.0),
+            egui::Layout::left_to_right(egui::Align::Center),
+            |ui| {
+                ui.set_max_width(text_edit_width);
+                styled_text_edit(ui, &mut wizard.installation_path, "C:\\...\\FPSAimTrainer");
+            }
+        );
+        if styled_button(ui, "Browse", false).clicked() {
+            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                wizard.installation_path = path.display().to_string();
+            }
+        }
+    });
+    ui.add_space(4.0);
+    let (text, color) = match wizard.stats_files {
+        Some(0) => ("Stats folder found, no runs in it yet".to_string(), TEXT_MUTED),
+        Some(count) => (format!("Stats folder found with {} runs", count), STATUS_GREEN),
+        None => ("No stats folder in this folder".to_string(), STATUS_RED),
+    };
+    ui.label(RichText::new(text).size(12.0).color(color));
+}
+fn render_account_step(ui: &mut egui::Ui, wizard: &mut SetupWizard) {
+    section_header(ui, "Your Stats");
+    ui.label(RichText::new("KovaaK's webapp username (optional)").size(13.0).color(TEXT_WHITE));
+    ui.add_space(4.0);
+    styled_text_edit(ui, &mut wizard.webapp_username, "Username");
+    ui.add_space(12.0);
+    let runs = wizard.stats_files.unwrap_or(0);
+    styled_checkbox(ui, &mut wizard.import_stats, &format!("Import highscores from {} runs now", runs));
+    #[cfg(feature = "online")]
+    ui.add_enabled_ui(!wizard.webapp_username.trim().is_empty(), |ui| {
+        styled_checkbox(ui, &mut wizard.sync_online, "Sync my online highscores");
+    });
+    ui.add_space(8.0);
+    ui.label(RichText::new("Everything here can be changed in Settings later").size(11.0).color(TEXT_DISABLED));
+}
\ No newline at end of file
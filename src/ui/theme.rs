@@ -9,6 +9,7 @@ pub const BORDER_SUBTLE: Color32 = Color32::from_rgba_premultiplied(255, 255, 25
 pub const BORDER_SECONDARY: Color32 = Color32::from_rgba_premultiplied(255, 255, 255, 102);
 pub const STATUS_GREEN: Color32 = Color32::from_rgb(16, 185, 129);
 pub const STATUS_RED: Color32 = Color32::from_rgb(239, 68, 68);
+pub const STATUS_YELLOW: Color32 = Color32::from_rgb(245, 158, 11);
 pub const HOVER_BG: Color32 = Color32::from_rgba_premultiplied(255, 255, 255, 13);
 pub fn apply_dark_theme(ctx: &egui::Context) {
 
@@ -82,6 +83,22 @@ pub fn styled_text_edit(ui: &mut egui::Ui, text: &mut String, hint: &str) -> egu
         })
         .inner
 }
+pub fn styled_multiline_edit(ui: &mut egui::Ui, text: &mut String, hint: &str) -> egui::Response {
+    let text_edit = egui::TextEdit::multiline(text)
+        .desired_width(ui.available_width())
+        .desired_rows(3)
+        .hint_text(RichText::new(hint).color(TEXT_DISABLED))
+        .margin(Vec2::new(12.0, 8.0))
+        .text_color(TEXT_WHITE);
+    egui::Frame::none()
+        .fill(BG_BLACK)
+        .stroke(Stroke::new(1.0, BORDER_SECONDARY))
+        .rounding(Rounding::same(8.0))
+        .show(ui, |ui| {
+            ui.add(text_edit)
+        })
+        .inner
+}
 pub fn card_frame() -> egui::Frame {
     egui::Frame::none()
         .fill(BG_DARK)
@@ -0,0 +1,64 @@
+use crate::backend::{get_stats_directory, scenario_key};
+use crate::state::{AppState, UiUpdate};
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+/// Files parsed between two progress updates.
+const PROGRESS_EVERY: usize = 250;
+/// Brings the stats index up to date in the background and imports the highscores it holds.
+/// Does nothing while a scan is already running, [`cancel_stats_scan`] stops it.
+pub fn start_stats_scan(state: Arc<AppState>) {
+    if state.stats_scan_in_progress.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    state.stats_scan_cancel.store(false, Ordering::Relaxed);
+    let worker_state = state.clone();
+    state.track_worker(thread::spawn(move || {
+        let message = run_scan(&worker_state);
+        worker_state.stats_scan_in_progress.store(false, Ordering::Relaxed);
+        worker_state.send_ui_update(UiUpdate::Toast { message });
+    }));
+}
+pub fn cancel_stats_scan(state: &AppState) {
+    state.stats_scan_cancel.store(true, Ordering::Relaxed);
+}
+fn run_scan(state: &AppState) -> String {
+    let stats_dir = get_stats_directory(&state.settings.lock());
+    if !stats_dir.exists() {
+        return "Stats folder not found".to_string();
+    }
+    let progress = |done: usize, total: usize| {
+        if done % PROGRESS_EVERY == 0 || done == total {
+            state.send_ui_update(UiUpdate::SyncProgress {
+                message: format!("Scanning stats {}/{}", done, total),
+            });
+        }
+    };
+    let update = match state
+        .stats_index
+        .update(&stats_dir, &state.stats_scan_cancel, &progress)
+    {
+        Ok(update) => update,
+        Err(e) => return format!("Scan failed: {}", e),
+    };
+    if update.cancelled {
+        return format!("Scan cancelled after {} files, the next scan continues from there", update.parsed);
+    }
+    let unreadable = update.index.entries.values().filter(|entry| entry.score.is_none()).count();
+    let scores = update.index.best_scores();
+    let count = scores.keys().map(|(name, _)| scenario_key(name)).collect::<HashSet<_>>().len();
+    if let Err(e) = state.local_scores_manager.populate_from_stats_folder(scores) {
+        return format!("Failed to save scores: {}", e);
+    }
+    if let Ok(all_scores) = state.local_scores_manager.get_all_scores() {
+        *state.score_cache.lock() = all_scores;
+    }
+    state.refresh_current_highscore();
+    state.send_ui_update(UiUpdate::ScoresUpdated);
+    if unreadable > 0 {
+        format!("Imported {} scenarios, {} stats files couldn't be read", count, unreadable)
+    } else {
+        format!("Imported {} scenarios", count)
+    }
+}
\ No newline at end of file
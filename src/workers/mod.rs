@@ -1,2 +1,11 @@
+//! Background monitoring of the game, the stats indexer and the session state machine.
+pub mod indexer;
 pub mod monitoring;
-pub use monitoring::start_monitoring_thread;
\ No newline at end of file
+pub mod session;
+#[cfg(feature = "online")]
+pub mod webhook;
+pub use indexer::{cancel_stats_scan, start_stats_scan};
+pub use monitoring::{start_monitoring_thread, start_rpc, stop_rpc};
+pub use session::{SessionEvent, SessionState, SessionStateMachine};
+#[cfg(feature = "online")]
+pub use webhook::start_webhook_thread;
\ No newline at end of file
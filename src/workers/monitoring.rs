@@ -1,14 +1,57 @@
 use crate::backend::{
-    find_fight_time_and_score, find_initial_scores, get_current_scenario, get_playlist_share_code,
-    get_stats_directory, is_kovaaks_running, normalize_scenario_name, ScoreSource,
+    build_presence, display_score, find_fight_time_and_score, find_initial_scores,
+    format_goal_progress, format_highscore, get_playlist_progress, get_stats_directory,
+    idle_details, normalize_scenario_name, scenario_key, DiscordRPC, ObsOutput, ObsSnapshot,
+    PresenceInput, ProcessWatcher, RunMode, RunRecord, ScoreSource, Settings,
 };
 use crate::state::{AppState, UiUpdate};
+use crate::workers::session::{
+    KovaaksFiles, SessionEvent, SessionState, SessionStateMachine, SystemClock,
+};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
+/// How often [`pause`] checks whether RPC was stopped.
+const PAUSE_STEP: Duration = Duration::from_millis(250);
+pub fn start_rpc(state: Arc<AppState>) -> Result<(), String> {
+    if state.is_rpc_running() {
+        return Ok(());
+    }
+    if state.is_shutting_down() {
+        return Err("The app is shutting down".to_string());
+    }
+    let mut rpc = DiscordRPC::new()?;
+    rpc.connect()?;
+    *state.rpc.lock() = Some(rpc);
+    state.rpc_running.store(true, Ordering::Relaxed);
+    *state.start_time.lock() = Some(chrono::Utc::now().timestamp());
+    state.reset_session(std::time::SystemTime::now());
+    let catch_up_state = state.clone();
+    state.track_worker(thread::spawn(move || catch_up_state.import_missed_runs()));
+    let worker_state = state.clone();
+    state.track_worker(thread::spawn(move || {
+        start_monitoring_thread(worker_state);
+    }));
+    Ok(())
+}
+pub fn stop_rpc(state: &AppState) {
+    state.rpc_running.store(false, Ordering::Relaxed);
+    if let Some(mut rpc) = state.rpc.lock().take() {
+        let _ = rpc.clear_presence();
+        let _ = rpc.disconnect();
+    }
+    *state.start_time.lock() = None;
+    state.clear_current_scenario();
+}
 pub fn start_monitoring_thread(state: Arc<AppState>) {
-
+    let mut process_watcher = ProcessWatcher::new();
+    let installation_path = state.settings.lock().installation_path.clone();
+    let mut session = SessionStateMachine::new(SystemClock, KovaaksFiles { installation_path });
+    let mut obs_output = ObsOutput::default();
+    if state.training_calendar.lock().is_none() {
+        state.refresh_calendar();
+    }
     loop {
 
         if !state.rpc_running.load(Ordering::Relaxed) {
@@ -21,162 +64,345 @@ pub fn start_monitoring_thread(state: Arc<AppState>) {
             continue;
         }
 
-        let kovaaks_running = is_kovaaks_running();
-        let was_running = state.kovaaks_was_running.swap(kovaaks_running, Ordering::Relaxed);
-        if kovaaks_running {
+        let settings = state.settings.lock().clone();
+        session.files_mut().installation_path = settings.installation_path.clone();
+        session.set_idle_after(settings.idle_timeout());
 
-            if !was_running {
+        let mut events = Vec::new();
+        if let Some(process_event) = process_watcher.poll() {
+            events.extend(session.on_process_event(process_event));
+        }
+        events.extend(session.tick());
+        state
+            .kovaaks_was_running
+            .store(process_watcher.is_running(), Ordering::Relaxed);
 
-                *state.session_start_time.lock() = std::time::SystemTime::now();
-                state.session_best_scores.lock().clear();
-                state.checked_files.lock().clear();
-            }
+        apply_session_events(&state, &session, &events);
 
-            {
-                let mut rpc_guard = state.rpc.lock();
-                if let Some(rpc) = rpc_guard.as_mut() {
-                    if !rpc.is_connected() {
+        if !session.state().is_game_running() || matches!(session.state(), SessionState::Idle { .. }) {
+            update_obs_output(&mut obs_output, &state, &settings, &session, None);
+            pause(&state, Duration::from_secs(10));
+            continue;
+        }
 
-                        if rpc.connect().is_err() {
-                            thread::sleep(Duration::from_secs(5));
-                            continue;
-                        }
-                    }
-                }
-            }
+        {
+            let mut rpc_guard = state.rpc.lock();
+            if let Some(rpc) = rpc_guard.as_mut() {
+                if !rpc.is_connected() {
 
-            let scenario = match get_current_scenario() {
-                Ok(s) => normalize_scenario_name(&s),
-                Err(_) => {
-                    thread::sleep(Duration::from_secs(5));
-                    continue;
+                    if rpc.connect().is_err() {
+                        pause(&state, Duration::from_secs(5));
+                        continue;
+                    }
                 }
-            };
-            if scenario.is_empty() || scenario == "Unknown Scenario" {
-                thread::sleep(Duration::from_secs(5));
-                continue;
             }
+        }
 
-            if !state.is_scenario_allowed(&scenario) {
-                thread::sleep(Duration::from_secs(5));
+        let (game_name, scenario) = match session.state().scenario() {
+            Some(s) => (s.to_string(), normalize_scenario_name(s)),
+            None => {
+                update_obs_output(&mut obs_output, &state, &settings, &session, None);
+                pause(&state, Duration::from_secs(5));
                 continue;
             }
-            let current = state.current_scenario.lock().clone();
-            let scenario_changed = current != scenario;
-            if scenario_changed {
-
-                *state.current_scenario.lock() = scenario.clone();
+        };
 
-                let settings = state.settings.lock().clone();
-                let stats_dir = get_stats_directory(&settings);
+        let key = scenario_key(&scenario);
+        let stats_dir = get_stats_directory(&settings);
+        let current = state.current_scenario.lock().clone();
+        if current != scenario {
 
-                let cached_highscore = state.get_score_for_scenario(&scenario);
-                *state.local_highscore.lock() = cached_highscore;
+            *state.current_scenario.lock() = scenario.clone();
 
-                let session_best = state.session_best_scores.lock()
-                    .get(&scenario)
-                    .copied()
-                    .unwrap_or(0.0);
-                *state.session_highscore.lock() = session_best;
+            let cached_highscore = state.get_score_for_scenario(&scenario);
+            state
+                .session_start_highscores
+                .lock()
+                .entry(key.clone())
+                .or_insert(cached_highscore);
+            *state.local_highscore.lock() = cached_highscore;
 
-                if let Ok((initial_score, files)) = find_initial_scores(&scenario, &stats_dir) {
-                    if initial_score > cached_highscore {
-                        *state.local_highscore.lock() = initial_score;
+            let session_best = state.session_best_scores.lock()
+                .get(&key)
+                .copied()
+                .unwrap_or(0.0);
+            *state.session_highscore.lock() = session_best;
 
+            if let Ok(initial) = find_initial_scores(&game_name, &stats_dir) {
+                for mode in RunMode::ALL {
+                    let best = initial.mode_best(mode);
+                    if best > 0.0 {
                         let _ = state.local_scores_manager.update_score(
                             &scenario,
-                            initial_score,
+                            best,
                             None,
                             ScoreSource::Local,
+                            Some(mode),
                         );
                     }
-                    *state.checked_files.lock() = files;
                 }
-                state.send_ui_update(UiUpdate::ScenarioChanged {
-                    name: scenario.clone(),
-                    highscore: *state.local_highscore.lock(),
-                    session_best: *state.session_highscore.lock(),
-                });
+                if let Ok(all_scores) = state.local_scores_manager.get_all_scores() {
+                    *state.score_cache.lock() = all_scores;
+                }
+                let initial_score = initial.best(settings.counted_modes);
+                if initial_score > cached_highscore {
+                    *state.local_highscore.lock() = initial_score;
+                }
+                state.report_unreadable_files(&initial.unreadable_files);
+                *state.checked_files.lock() = initial.checked_files;
             }
+            state.send_ui_update(UiUpdate::ScenarioChanged {
+                name: scenario.clone(),
+                highscore: *state.local_highscore.lock(),
+                session_best: *state.session_highscore.lock(),
+            });
+        }
 
-            let settings = state.settings.lock().clone();
-            let stats_dir = get_stats_directory(&settings);
-            let checked = state.checked_files.lock().clone();
-            if let Ok((new_score, found_new, last_played)) =
-                find_fight_time_and_score(&scenario, &stats_dir, &checked)
-            {
-                if found_new && new_score > 0.0 {
+        let checked = state.checked_files.lock().clone();
+        if let Ok(new_runs) = find_fight_time_and_score(&game_name, &stats_dir, &checked, settings.counted_modes) {
+            if !new_runs.unreadable_files.is_empty() {
+                state.report_unreadable_files(&new_runs.unreadable_files);
+                state.checked_files.lock().extend(new_runs.unreadable_files.iter().cloned());
+            }
+            if new_runs.found_new {
+
+                let events = session.record_activity();
+                apply_session_events(&state, &session, &events);
 
-                    if let Ok((_, new_files)) = find_initial_scores(&scenario, &stats_dir) {
-                        *state.checked_files.lock() = new_files;
+                if let Ok(initial) = find_initial_scores(&game_name, &stats_dir) {
+                    *state.checked_files.lock() = initial.checked_files;
+                }
+
+                if let Some(calendar) = state.training_calendar.lock().as_mut() {
+                    for run in &new_runs.runs {
+                        calendar.add_run(run.played_at.unwrap_or_else(SystemTime::now), run.stats.fight_time);
                     }
+                }
 
-                    {
-                        let mut session_bests = state.session_best_scores.lock();
-                        let current_session_best = session_bests.get(&scenario).copied().unwrap_or(0.0);
-                        if new_score > current_session_best {
-                            session_bests.insert(scenario.clone(), new_score);
-                            *state.session_highscore.lock() = new_score;
-                        }
+                let dpi = settings.mouse_dpi;
+                let records = new_runs
+                    .runs
+                    .iter()
+                    .map(|run| RunRecord::from_run(&run.stats, run.played_at, Some(run.mode), dpi))
+                    .collect();
+                if let Ok(added) = state.run_history.add_runs(&scenario, records) {
+                    if added > 0 {
+                        state.send_ui_update(UiUpdate::ScoresUpdated);
                     }
+                }
 
-                    let current_high = *state.local_highscore.lock();
-                    if new_score > current_high {
-                        *state.local_highscore.lock() = new_score;
+                if let Some(run) = new_runs.latest_run.clone() {
+                    state.session_last_runs.lock().insert(key.clone(), run);
+                }
+                *state.current_mode.lock() = new_runs.latest_mode();
 
+                for mode in RunMode::ALL {
+                    if let Some(run) = new_runs.best_of(mode) {
                         let _ = state.local_scores_manager.update_score(
                             &scenario,
-                            new_score,
-                            last_played,
+                            (run.stats.score * 10.0).round() / 10.0,
+                            run.played_at,
                             ScoreSource::Local,
+                            Some(mode),
                         );
+                    }
+                }
+                if let Ok(all_scores) = state.local_scores_manager.get_all_scores() {
+                    *state.score_cache.lock() = all_scores;
+                }
+            }
+            let new_score = new_runs.best_score;
+            if new_runs.found_new && new_score > 0.0 {
+                {
+                    let mut session_bests = state.session_best_scores.lock();
+                    let current_session_best = session_bests.get(&key).copied().unwrap_or(0.0);
+                    if new_score > current_session_best {
+                        session_bests.insert(key.clone(), new_score);
+                        *state.session_highscore.lock() = new_score;
+                    }
+                }
+
+                let current_high = *state.local_highscore.lock();
+                if new_score > current_high {
+                    *state.local_highscore.lock() = new_score;
 
-                        if let Ok(all_scores) = state.local_scores_manager.get_all_scores() {
-                            *state.score_cache.lock() = all_scores;
+                    if let Some(goal) = state.get_goal_for_scenario(&scenario) {
+                        let online = state.get_online_score_for_scenario(&scenario);
+                        let before = display_score(current_high, online, settings.show_online_scores);
+                        let after = display_score(new_score, online, settings.show_online_scores);
+                        if before < goal && after >= goal {
+                            state.send_ui_update(UiUpdate::Toast {
+                                message: format!("Goal reached on {}: {}", scenario, format_goal_progress(after, goal)),
+                            });
                         }
                     }
+
+                    let accuracy = new_runs
+                        .runs
+                        .iter()
+                        .filter(|run| settings.counted_modes.counts(run.mode))
+                        .map(|run| &run.stats)
+                        .max_by(|a, b| a.score.total_cmp(&b.score))
+                        .and_then(|run| run.accuracy());
+                    state.notify_personal_best(&scenario, current_high, new_score, accuracy);
                 }
             }
+        }
 
+        let visible = state.is_scenario_allowed(&scenario);
+        {
+            let mut rpc_guard = state.rpc.lock();
+            if let Some(rpc) = rpc_guard.as_mut() {
+                if visible {
+                    let last_run = state.session_last_runs.lock().get(&key).cloned();
+                    let input = PresenceInput {
+                        scenario_name: &scenario,
+                        game_name: &game_name,
+                        highscore: *state.local_highscore.lock(),
+                        online_highscore: state.get_online_score_for_scenario(&scenario),
+                        session_highscore: *state.session_highscore.lock(),
+                        share_code: session.state().share_code(),
+                        last_run: last_run.as_ref(),
+                        mode: *state.current_mode.lock(),
+                        goal: state.get_goal_for_scenario(&scenario),
+                        streak: state.current_streak(),
+                    };
+                    let content = build_presence(&input, &settings);
+                    let start_time = *state.start_time.lock();
+                    let _ = rpc.update_presence(&content, start_time);
+                } else {
+                    let _ = rpc.clear_presence();
+                }
+            }
+        }
+        let shown_scenario = if visible { Some(scenario.as_str()) } else { None };
+        update_obs_output(&mut obs_output, &state, &settings, &session, shown_scenario);
+        pause(&state, Duration::from_secs(10));
+    }
+    clear_obs_output(&mut obs_output, &state.settings.lock());
+
+}
+/// Sleeps for `duration`, returning early once RPC is stopped.
+fn pause(state: &AppState, duration: Duration) {
+    let deadline = Instant::now() + duration;
+    while state.is_rpc_running() && Instant::now() < deadline {
+        thread::sleep(PAUSE_STEP);
+    }
+}
+/// Blanks the OBS files once RPC stops, so the overlay doesn't keep showing the last scenario.
+fn clear_obs_output(obs_output: &mut ObsOutput, settings: &Settings) {
+    let Some(directory) = settings.obs_output_directory() else {
+        return;
+    };
+    if let Err(e) = obs_output.update(&directory, &ObsSnapshot::default()) {
+        log::warn!("Failed to clear OBS files: {}", e);
+    }
+}
+fn update_obs_output(
+    obs_output: &mut ObsOutput,
+    state: &AppState,
+    settings: &Settings,
+    session: &SessionStateMachine,
+    scenario: Option<&str>,
+) {
+    let Some(directory) = settings.obs_output_directory() else {
+        return;
+    };
+    let mut snapshot = ObsSnapshot {
+        status: session.state().label().to_string(),
+        ..Default::default()
+    };
+    if let Some(scenario) = scenario {
+        snapshot.scenario = if settings.streamer_mode {
+            "a scenario".to_string()
+        } else {
+            scenario.to_string()
+        };
+        if !settings.hide_scores {
+            snapshot.highscore = format_highscore(
+                *state.local_highscore.lock(),
+                state.get_online_score_for_scenario(scenario),
+                settings.show_online_scores,
+            );
+            let session_best = *state.session_highscore.lock();
+            if session_best > 0.0 {
+                snapshot.session_best = format!("{:.1}", session_best);
+            }
+        }
+        if let (Some(share_code), false) = (session.state().share_code(), settings.streamer_mode) {
+            let game_name = session.state().scenario().unwrap_or(scenario);
+            let progress = get_playlist_progress(&settings.installation_path, game_name).unwrap_or_default();
+            snapshot.playlist = progress.name.unwrap_or_else(|| share_code.to_string());
+            snapshot.playlist_progress = match progress.position {
+                Some(position) => format!("{}/{}", position, progress.total),
+                None => String::new(),
+            };
+        }
+    }
+    if let Err(e) = obs_output.update(&directory, &snapshot) {
+        log::warn!("Failed to write OBS files: {}", e);
+    }
+}
+fn apply_session_events(state: &AppState, session: &SessionStateMachine, events: &[SessionEvent]) {
+    if events.is_empty() {
+        return;
+    }
+    for event in events {
+        handle_session_event(state, session.state(), event);
+    }
+    *state.session_state.lock() = session.state().clone();
+    state.send_ui_update(UiUpdate::SessionStateChanged {
+        state: session.state().clone(),
+    });
+}
+fn handle_session_event(state: &AppState, session_state: &SessionState, event: &SessionEvent) {
+    match event {
+        SessionEvent::GameStarted { started_at } => {
+            state.reset_session(*started_at);
+        }
+        SessionEvent::GameClosed { duration } => {
+            if let Some(summary) = state.summarize_session(*duration) {
+                if let Err(e) = state.session_log.append(summary.clone()) {
+                    log::warn!("Failed to save session summary: {}", e);
+                }
+                state.notify_session_end(&summary);
+                state.send_ui_update(UiUpdate::SessionEnded { summary });
+            }
             {
                 let mut rpc_guard = state.rpc.lock();
                 if let Some(rpc) = rpc_guard.as_mut() {
-                    let highscore = *state.local_highscore.lock();
-                    let session_best = *state.session_highscore.lock();
-                    let start_time = *state.start_time.lock();
-                    let share_code = get_playlist_share_code(&settings.installation_path);
-                    let _ = rpc.update_presence(
-                        &scenario,
-                        start_time,
-                        highscore,
-                        session_best,
-                        &settings.installation_path,
-                        share_code,
-                    );
+                    let _ = rpc.clear_presence();
                 }
             }
-        } else {
 
-            if was_running {
-
-                {
-                    let mut rpc_guard = state.rpc.lock();
-                    if let Some(rpc) = rpc_guard.as_mut() {
-                        let _ = rpc.clear_presence();
-                    }
-                }
+            state.clear_current_scenario();
+            state.send_ui_update(UiUpdate::ScenarioChanged {
+                name: String::new(),
+                highscore: 0.0,
+                session_best: 0.0,
+            });
+        }
+        SessionEvent::WentIdle => {
+            let settings = state.settings.lock().clone();
+            let scenario = session_state.scenario().map(normalize_scenario_name);
+            let hidden = scenario.as_deref().is_some_and(|name| !state.is_scenario_allowed(name));
+            let mut rpc_guard = state.rpc.lock();
+            if let Some(rpc) = rpc_guard.as_mut() {
+                let _ = if settings.clear_presence_when_idle || hidden {
+                    rpc.clear_presence()
+                } else {
+                    rpc.update_idle_presence(&idle_details(scenario.as_deref(), &settings))
+                };
+            }
+        }
+        SessionEvent::Resumed { idle_for } => {
 
-                *state.current_scenario.lock() = String::new();
-                *state.local_highscore.lock() = 0.0;
-                *state.session_highscore.lock() = 0.0;
-                state.send_ui_update(UiUpdate::ScenarioChanged {
-                    name: String::new(),
-                    highscore: 0.0,
-                    session_best: 0.0,
-                });
+            if let Some(start_time) = state.start_time.lock().as_mut() {
+                *start_time += idle_for.as_secs() as i64;
             }
         }
-        thread::sleep(Duration::from_secs(10));
+        SessionEvent::EnteredMenu
+        | SessionEvent::ScenarioStarted { .. }
+        | SessionEvent::PlaylistStarted { .. } => {}
     }
-
 }
\ No newline at end of file
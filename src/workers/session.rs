@@ -0,0 +1,389 @@
+//! Game session state machine driven by process events and game files.
+use crate::backend::{
+    get_current_scenario, get_playlist_share_code, get_session_file_path, ProcessEvent,
+};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+/// Time source, swappable for a fake clock.
+pub trait Clock: Send {
+    fn now(&self) -> SystemTime;
+}
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+/// Game file access the state machine needs, swappable for a fake filesystem.
+pub trait GameFiles: Send {
+    fn current_scenario(&self) -> Option<String>;
+    fn playlist_share_code(&self) -> Option<String>;
+    fn activity_marker(&self) -> Option<SystemTime>;
+}
+pub struct KovaaksFiles {
+    pub installation_path: String,
+}
+impl GameFiles for KovaaksFiles {
+    fn current_scenario(&self) -> Option<String> {
+        let scenario = get_current_scenario().ok()?;
+        if scenario.trim().is_empty() || scenario == "Unknown Scenario" {
+            return None;
+        }
+        Some(scenario)
+    }
+    fn playlist_share_code(&self) -> Option<String> {
+        get_playlist_share_code(&self.installation_path)
+    }
+    fn activity_marker(&self) -> Option<SystemTime> {
+        let stats_dir = PathBuf::from(&self.installation_path).join("stats");
+        [get_session_file_path().ok(), Some(stats_dir)]
+            .into_iter()
+            .flatten()
+            .filter_map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+            .max()
+    }
+}
+/// Scenarios are named as the game reports them, see [`crate::backend::normalize_scenario_name`] for the stored name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionState {
+    GameClosed,
+    InMenu,
+    InScenario { scenario: String },
+    InPlaylist { scenario: String, share_code: String },
+    Idle { scenario: Option<String> },
+}
+impl SessionState {
+    pub fn scenario(&self) -> Option<&str> {
+        match self {
+            SessionState::InScenario { scenario } | SessionState::InPlaylist { scenario, .. } => Some(scenario),
+            SessionState::Idle { scenario } => scenario.as_deref(),
+            SessionState::GameClosed | SessionState::InMenu => None,
+        }
+    }
+    pub fn share_code(&self) -> Option<&str> {
+        match self {
+            SessionState::InPlaylist { share_code, .. } => Some(share_code),
+            _ => None,
+        }
+    }
+    pub fn is_game_running(&self) -> bool {
+        !matches!(self, SessionState::GameClosed)
+    }
+    pub fn label(&self) -> &'static str {
+        match self {
+            SessionState::GameClosed => "Game closed",
+            SessionState::InMenu => "In menu",
+            SessionState::InScenario { .. } => "In scenario",
+            SessionState::InPlaylist { .. } => "In playlist",
+            SessionState::Idle { .. } => "Idle",
+        }
+    }
+}
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    GameStarted { started_at: SystemTime },
+    GameClosed { duration: Duration },
+    EnteredMenu,
+    ScenarioStarted { scenario: String },
+    PlaylistStarted { scenario: String, share_code: String },
+    WentIdle,
+    Resumed { idle_for: Duration },
+}
+/// Tracks GameClosed → InMenu → InScenario / InPlaylist → Idle and emits [`SessionEvent`]s.
+pub struct SessionStateMachine<C: Clock = SystemClock, F: GameFiles = KovaaksFiles> {
+    clock: C,
+    files: F,
+    state: SessionState,
+    session_started_at: Option<SystemTime>,
+    last_activity: SystemTime,
+    last_marker: Option<SystemTime>,
+    idle_after: Option<Duration>,
+    idle_since: Option<SystemTime>,
+    idle_total: Duration,
+}
+impl<C: Clock, F: GameFiles> SessionStateMachine<C, F> {
+    pub fn new(clock: C, files: F) -> Self {
+        let now = clock.now();
+        Self {
+            clock,
+            files,
+            state: SessionState::GameClosed,
+            session_started_at: None,
+            last_activity: now,
+            last_marker: None,
+            idle_after: None,
+            idle_since: None,
+            idle_total: Duration::ZERO,
+        }
+    }
+    pub fn state(&self) -> &SessionState {
+        &self.state
+    }
+    pub fn files_mut(&mut self) -> &mut F {
+        &mut self.files
+    }
+    pub fn active_duration(&self) -> Duration {
+        let now = self.clock.now();
+        let total = self
+            .session_started_at
+            .and_then(|start| now.duration_since(start).ok())
+            .unwrap_or_default();
+        total.saturating_sub(self.idle_total + self.current_idle_duration(now))
+    }
+    pub fn set_idle_after(&mut self, idle_after: Option<Duration>) {
+        self.idle_after = idle_after;
+    }
+    pub fn on_process_event(&mut self, event: ProcessEvent) -> Vec<SessionEvent> {
+        let now = self.clock.now();
+        match event {
+            ProcessEvent::Started { launched_at, .. } => {
+                let started_at = if launched_at > 0 {
+                    UNIX_EPOCH + Duration::from_secs(launched_at)
+                } else {
+                    now
+                };
+                self.session_started_at = Some(started_at);
+                self.last_activity = now;
+                self.last_marker = None;
+                self.idle_since = None;
+                self.idle_total = Duration::ZERO;
+                self.state = SessionState::InMenu;
+                vec![SessionEvent::GameStarted { started_at }]
+            }
+            ProcessEvent::Exited { .. } => {
+                if !self.state.is_game_running() {
+                    return Vec::new();
+                }
+                let duration = self.active_duration();
+                self.session_started_at = None;
+                self.idle_since = None;
+                self.state = SessionState::GameClosed;
+                vec![SessionEvent::GameClosed { duration }]
+            }
+        }
+    }
+    pub fn record_activity(&mut self) -> Vec<SessionEvent> {
+        let now = self.clock.now();
+        self.last_activity = now;
+        if !matches!(self.state, SessionState::Idle { .. }) {
+            return Vec::new();
+        }
+        let next = self.observe();
+        self.resume(now, next)
+    }
+    pub fn tick(&mut self) -> Vec<SessionEvent> {
+        if !self.state.is_game_running() {
+            return Vec::new();
+        }
+        let now = self.clock.now();
+        let marker = self.files.activity_marker();
+        let marker_changed = self.last_marker.is_some() && marker != self.last_marker;
+        self.last_marker = marker;
+        let next = self.observe();
+        if let SessionState::Idle { scenario } = &self.state {
+            if !marker_changed && next.scenario() == scenario.as_deref() {
+                return Vec::new();
+            }
+            self.last_activity = now;
+            return self.resume(now, next);
+        }
+        let mut events = self.transition_to(next);
+        if marker_changed || !events.is_empty() {
+            self.last_activity = now;
+            return events;
+        }
+        if let Some(idle_after) = self.idle_after {
+            let inactive_for = now.duration_since(self.last_activity).unwrap_or_default();
+            if inactive_for >= idle_after {
+                self.state = SessionState::Idle {
+                    scenario: self.state.scenario().map(str::to_string),
+                };
+                self.idle_since = Some(now);
+                events.push(SessionEvent::WentIdle);
+            }
+        }
+        events
+    }
+    fn resume(&mut self, now: SystemTime, next: SessionState) -> Vec<SessionEvent> {
+        let idle_for = self.current_idle_duration(now);
+        self.idle_total += idle_for;
+        self.idle_since = None;
+        let mut events = vec![SessionEvent::Resumed { idle_for }];
+        events.extend(self.transition_to(next));
+        events
+    }
+    fn current_idle_duration(&self, now: SystemTime) -> Duration {
+        self.idle_since
+            .and_then(|since| now.duration_since(since).ok())
+            .unwrap_or_default()
+    }
+    fn observe(&self) -> SessionState {
+        match self.files.current_scenario() {
+            None => SessionState::InMenu,
+            Some(scenario) => match self.files.playlist_share_code() {
+                Some(share_code) => SessionState::InPlaylist { scenario, share_code },
+                None => SessionState::InScenario { scenario },
+            },
+        }
+    }
+    fn transition_to(&mut self, next: SessionState) -> Vec<SessionEvent> {
+        if next == self.state {
+            return Vec::new();
+        }
+        let event = match &next {
+            SessionState::InMenu => Some(SessionEvent::EnteredMenu),
+            SessionState::InScenario { scenario } => Some(SessionEvent::ScenarioStarted {
+                scenario: scenario.clone(),
+            }),
+            SessionState::InPlaylist { scenario, share_code } => Some(SessionEvent::PlaylistStarted {
+                scenario: scenario.clone(),
+                share_code: share_code.clone(),
+            }),
+            SessionState::GameClosed | SessionState::Idle { .. } => None,
+        };
+        self.state = next;
+        event.into_iter().collect()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    const IDLE_AFTER: Duration = Duration::from_secs(60);
+    #[derive(Clone)]
+    struct FakeClock(Arc<Mutex<SystemTime>>);
+    impl FakeClock {
+        fn advance(&self, by: Duration) {
+            *self.0.lock().unwrap() += by;
+        }
+    }
+    impl Clock for FakeClock {
+        fn now(&self) -> SystemTime {
+            *self.0.lock().unwrap()
+        }
+    }
+    #[derive(Default)]
+    struct FakeFiles {
+        scenario: Option<String>,
+        share_code: Option<String>,
+        marker: Option<SystemTime>,
+    }
+    impl GameFiles for FakeFiles {
+        fn current_scenario(&self) -> Option<String> {
+            self.scenario.clone()
+        }
+        fn playlist_share_code(&self) -> Option<String> {
+            self.share_code.clone()
+        }
+        fn activity_marker(&self) -> Option<SystemTime> {
+            self.marker
+        }
+    }
+    fn launched_at() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+    }
+    fn started_machine() -> (FakeClock, SessionStateMachine<FakeClock, FakeFiles>) {
+        let clock = FakeClock(Arc::new(Mutex::new(launched_at())));
+        let mut machine = SessionStateMachine::new(clock.clone(), FakeFiles::default());
+        machine.set_idle_after(Some(IDLE_AFTER));
+        let events = machine.on_process_event(ProcessEvent::Started {
+            pid: 1,
+            launched_at: 1_700_000_000,
+        });
+        assert_eq!(events, vec![SessionEvent::GameStarted { started_at: launched_at() }]);
+        (clock, machine)
+    }
+    #[test]
+    fn walks_through_a_whole_session() {
+        let clock = FakeClock(Arc::new(Mutex::new(launched_at())));
+        let mut machine = SessionStateMachine::new(clock.clone(), FakeFiles::default());
+        machine.set_idle_after(Some(IDLE_AFTER));
+        assert_eq!(machine.state(), &SessionState::GameClosed);
+        assert!(machine.tick().is_empty());
+
+        let events = machine.on_process_event(ProcessEvent::Started {
+            pid: 1,
+            launched_at: 1_700_000_000,
+        });
+        assert_eq!(events, vec![SessionEvent::GameStarted { started_at: launched_at() }]);
+        assert_eq!(machine.state(), &SessionState::InMenu);
+
+        clock.advance(Duration::from_secs(1));
+        machine.files_mut().scenario = Some("Gridshot".to_string());
+        machine.files_mut().marker = Some(clock.now());
+        assert_eq!(
+            machine.tick(),
+            vec![SessionEvent::ScenarioStarted { scenario: "Gridshot".to_string() }]
+        );
+        assert_eq!(machine.state(), &SessionState::InScenario { scenario: "Gridshot".to_string() });
+
+        machine.files_mut().share_code = Some("KovaaKsCode".to_string());
+        let playlist = SessionState::InPlaylist {
+            scenario: "Gridshot".to_string(),
+            share_code: "KovaaKsCode".to_string(),
+        };
+        assert_eq!(
+            machine.tick(),
+            vec![SessionEvent::PlaylistStarted {
+                scenario: "Gridshot".to_string(),
+                share_code: "KovaaKsCode".to_string(),
+            }]
+        );
+        assert_eq!(machine.state(), &playlist);
+
+        clock.advance(IDLE_AFTER - Duration::from_secs(1));
+        assert!(machine.tick().is_empty());
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(machine.tick(), vec![SessionEvent::WentIdle]);
+        assert_eq!(machine.state(), &SessionState::Idle { scenario: Some("Gridshot".to_string()) });
+
+        clock.advance(Duration::from_secs(30));
+        assert!(machine.tick().is_empty());
+        machine.files_mut().marker = Some(clock.now());
+        assert_eq!(
+            machine.tick(),
+            vec![
+                SessionEvent::Resumed { idle_for: Duration::from_secs(30) },
+                SessionEvent::PlaylistStarted {
+                    scenario: "Gridshot".to_string(),
+                    share_code: "KovaaKsCode".to_string(),
+                },
+            ]
+        );
+        assert_eq!(machine.state(), &playlist);
+
+        assert_eq!(
+            machine.on_process_event(ProcessEvent::Exited { pid: 1 }),
+            vec![SessionEvent::GameClosed { duration: Duration::from_secs(61) }]
+        );
+        assert_eq!(machine.state(), &SessionState::GameClosed);
+        assert!(machine.on_process_event(ProcessEvent::Exited { pid: 1 }).is_empty());
+    }
+    #[test]
+    fn activity_resumes_idle_in_menu() {
+        let (clock, mut machine) = started_machine();
+        clock.advance(IDLE_AFTER);
+        assert_eq!(machine.tick(), vec![SessionEvent::WentIdle]);
+        assert_eq!(machine.state(), &SessionState::Idle { scenario: None });
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(
+            machine.record_activity(),
+            vec![
+                SessionEvent::Resumed { idle_for: Duration::from_secs(5) },
+                SessionEvent::EnteredMenu,
+            ]
+        );
+        assert_eq!(machine.state(), &SessionState::InMenu);
+        assert!(machine.record_activity().is_empty());
+    }
+    #[test]
+    fn never_goes_idle_without_a_timeout() {
+        let (clock, mut machine) = started_machine();
+        machine.set_idle_after(None);
+        clock.advance(Duration::from_secs(3600));
+        assert!(machine.tick().is_empty());
+        assert_eq!(machine.state(), &SessionState::InMenu);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,33 @@
+use crate::backend::{post_webhook, webhook::MIN_POST_INTERVAL, PostOutcome};
+use crate::state::AppState;
+use std::sync::Arc;
+use std::thread;
+/// Drains the webhook queue in the background, one post per [`MIN_POST_INTERVAL`], until the app shuts down.
+pub fn start_webhook_thread(state: Arc<AppState>) {
+    let worker_state = state.clone();
+    state.track_worker(thread::spawn(move || webhook_loop(worker_state)));
+}
+fn webhook_loop(state: Arc<AppState>) {
+    while !state.is_shutting_down() {
+        thread::sleep(MIN_POST_INTERVAL);
+        let url = state.settings.lock().webhook_url.trim().to_string();
+        if url.is_empty() {
+            continue;
+        }
+        let Some((index, payload)) = state.webhook_queue.lock().next_due() else {
+            continue;
+        };
+        let outcome = post_webhook(&url, &payload);
+        match &outcome {
+            PostOutcome::Sent => {}
+            PostOutcome::RateLimited(retry_after) => {
+                log::info!("Webhook rate limited, retrying in {:?}", retry_after);
+            }
+            PostOutcome::Retry(reason) => log::warn!("Webhook post failed, will retry: {}", reason),
+            PostOutcome::Rejected(reason) => log::warn!("Webhook post dropped: {}", reason),
+        }
+        if let Err(e) = state.webhook_queue.lock().complete(index, &outcome) {
+            log::warn!("Failed to save webhook queue: {}", e);
+        }
+    }
+}
\ No newline at end of file